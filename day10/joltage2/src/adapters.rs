@@ -0,0 +1,197 @@
+use std::error::Error;
+use std::fmt::Display;
+
+pub const DEFAULT_MIN_GAP: usize = 1;
+pub const DEFAULT_MAX_GAP: usize = 3;
+
+pub struct Adapters {
+    joltages: Vec<usize>,
+    edges: Vec<Vec<usize>>
+}
+
+impl Adapters {
+    // joltages holds just the adapters from the bag; the outlet (0 jolts) and
+    // the device (max_gap above the highest adapter) are added here, which
+    // fails if the device's joltage doesn't fit in a usize.
+    pub fn new(joltages: &[usize], min_gap: usize, max_gap: usize) -> Result<Adapters, CreateAdaptersError> {
+        let mut sorted = Vec::with_capacity(joltages.len() + 2);
+
+        sorted.push(0);
+        sorted.extend_from_slice(joltages);
+        sorted.sort_unstable();
+        let device = sorted.last().unwrap().checked_add(max_gap)
+            .ok_or(CreateAdaptersError::DeviceOverflow)?;
+        sorted.push(device);
+
+        let mut edges = Vec::with_capacity(sorted.len());
+
+        for (i, adapter) in sorted.iter().enumerate() {
+            let mut edges_for_adapter = Vec::new();
+
+            for (look_forward, forward) in sorted.iter().enumerate().skip(i + 1) {
+                let gap = forward - adapter;
+                if gap > max_gap {
+                    break;
+                }
+
+                if gap >= min_gap {
+                    edges_for_adapter.push(look_forward);
+                }
+            }
+
+            edges.push(edges_for_adapter);
+        }
+
+        Ok(Adapters { joltages: sorted, edges })
+    }
+
+    pub fn len(&self) -> usize {
+        self.joltages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.joltages.is_empty()
+    }
+
+    // Joltages in chain order, including the outlet and the device.
+    pub fn joltages(&self) -> &[usize] {
+        &self.joltages
+    }
+
+    // Indices into joltages() reachable in one step from the adapter at index.
+    pub fn edges(&self, index: usize) -> &[usize] {
+        &self.edges[index]
+    }
+
+    pub fn device(&self) -> usize {
+        self.joltages.len() - 1
+    }
+
+    pub fn count_arrangements(&self) -> Result<u128, ArrangementError> {
+        Ok(self.arrangement_counts()?[0])
+    }
+
+    // For every adapter, the number of distinct chains from it to the device.
+    pub fn arrangement_counts(&self) -> Result<Vec<u128>, ArrangementError> {
+        let mut counts = vec![0u128; self.joltages.len()];
+        counts[self.device()] = 1;
+
+        for i in (0..self.device()).rev() {
+            let mut accum: u128 = 0;
+            for edge in self.edges[i].iter() {
+                accum = accum.checked_add(counts[*edge]).ok_or(ArrangementError::Overflow)?;
+            }
+
+            counts[i] = accum;
+        }
+
+        Ok(counts)
+    }
+
+    // Whether the device can be reached from each adapter; unlike
+    // arrangement_counts this can't overflow.
+    pub fn reaches_device(&self) -> Vec<bool> {
+        let mut reaches = vec![false; self.joltages.len()];
+        reaches[self.device()] = true;
+
+        for i in (0..self.device()).rev() {
+            reaches[i] = self.edges[i].iter().any(|edge| reaches[*edge]);
+        }
+
+        reaches
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ArrangementError {
+    Overflow
+}
+
+impl Display for ArrangementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            ArrangementError::Overflow => "arrangement count doesn't fit in 128 bits"
+        })
+    }
+}
+
+impl Error for ArrangementError { }
+
+#[derive(Debug, PartialEq)]
+pub enum CreateAdaptersError {
+    DeviceOverflow
+}
+
+impl Display for CreateAdaptersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            CreateAdaptersError::DeviceOverflow => "device joltage is too large, try a smaller max gap"
+        })
+    }
+}
+
+impl Error for CreateAdaptersError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL_EXAMPLE: [usize; 11] = [16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+    const LARGE_EXAMPLE: [usize; 31] = [
+        28, 33, 18, 42, 31, 14, 46, 20, 48, 47, 24, 23, 49, 45, 19, 38,
+        39, 11, 1, 32, 25, 35, 8, 17, 7, 9, 4, 2, 34, 10, 3
+    ];
+
+    #[test]
+    fn example_arrangements() {
+        let small = Adapters::new(&SMALL_EXAMPLE, DEFAULT_MIN_GAP, DEFAULT_MAX_GAP).unwrap();
+        let large = Adapters::new(&LARGE_EXAMPLE, DEFAULT_MIN_GAP, DEFAULT_MAX_GAP).unwrap();
+
+        assert_eq!(Ok(8), small.count_arrangements());
+        assert_eq!(Ok(19208), large.count_arrangements());
+    }
+
+    #[test]
+    fn device_follows_max_gap() {
+        let adapters = Adapters::new(&[1, 2], DEFAULT_MIN_GAP, 5).unwrap();
+
+        assert_eq!(&[0, 1, 2, 7], adapters.joltages());
+        assert_eq!(&[1, 2], adapters.edges(0));
+    }
+
+    #[test]
+    fn min_gap_skips_small_steps() {
+        // 0 -> 2 -> 4 -> 7 is the only chain when 1-jolt steps are refused.
+        let adapters = Adapters::new(&[1, 2, 3, 4], 2, DEFAULT_MAX_GAP).unwrap();
+
+        assert_eq!(Ok(1), adapters.count_arrangements());
+    }
+
+    #[test]
+    fn unreachable_device_has_no_arrangements() {
+        let adapters = Adapters::new(&[1, 10], DEFAULT_MIN_GAP, DEFAULT_MAX_GAP).unwrap();
+
+        assert_eq!(Ok(0), adapters.count_arrangements());
+    }
+
+    #[test]
+    fn counts_beyond_u64() {
+        let joltages: Vec<usize> = (1..=100).collect();
+        let adapters = Adapters::new(&joltages, DEFAULT_MIN_GAP, DEFAULT_MAX_GAP).unwrap();
+
+        assert!(adapters.count_arrangements().unwrap() > u64::MAX as u128);
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let joltages: Vec<usize> = (1..=300).collect();
+        let adapters = Adapters::new(&joltages, DEFAULT_MIN_GAP, DEFAULT_MAX_GAP).unwrap();
+
+        assert_eq!(Err(ArrangementError::Overflow), adapters.count_arrangements());
+    }
+
+    #[test]
+    fn huge_max_gap_is_rejected() {
+        assert_eq!(Some(CreateAdaptersError::DeviceOverflow), Adapters::new(&[1, 2], DEFAULT_MIN_GAP, usize::MAX).err());
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::adapters::{Adapters, ArrangementError};
+
+#[derive(Debug, PartialEq)]
+pub struct Chain {
+    joltages: Vec<usize>
+}
+
+impl Chain {
+    fn from_indices(adapters: &Adapters, indices: &[usize]) -> Chain {
+        let joltages = indices.iter()
+            .map(|index| adapters.joltages()[*index])
+            .collect();
+
+        Chain { joltages }
+    }
+
+    // Joltages from the outlet to the device, inclusive.
+    pub fn joltages(&self) -> &[usize] {
+        &self.joltages
+    }
+
+    // Number of adapters used, not counting the outlet and the device.
+    pub fn adapter_count(&self) -> usize {
+        self.joltages.len() - 2
+    }
+
+    // How many steps of each jolt difference the chain takes.
+    pub fn differences(&self) -> BTreeMap<usize, usize> {
+        let mut differences = BTreeMap::new();
+
+        for pair in self.joltages.windows(2) {
+            *differences.entry(pair[1] - pair[0]).or_insert(0) += 1;
+        }
+
+        differences
+    }
+}
+
+impl Display for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let joltages: Vec<String> = self.joltages.iter().map(|joltage| joltage.to_string()).collect();
+
+        write!(f, "{}", joltages.join(" -> "))
+    }
+}
+
+// Walks every chain from the outlet to the device in lexicographic order
+// without materializing them up front. Adapters that can't reach the device
+// are never entered, so each call to next() does work proportional to the
+// chain length rather than the number of dead ends.
+pub struct Chains<'a> {
+    adapters: &'a Adapters,
+    reaches_device: Vec<bool>,
+    path: Vec<usize>,
+    cursors: Vec<usize>,
+    emitted: bool
+}
+
+impl<'a> Chains<'a> {
+    pub fn new(adapters: &'a Adapters) -> Chains<'a> {
+        let reaches_device = adapters.reaches_device();
+        let (path, cursors) = if reaches_device[0] {
+            (vec![0], vec![0])
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        Chains { adapters, reaches_device, path, cursors, emitted: false }
+    }
+}
+
+impl<'a> Iterator for Chains<'a> {
+    type Item = Chain;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let device = self.adapters.device();
+
+        while let Some(&node) = self.path.last() {
+            if node == device && !self.emitted {
+                self.emitted = true;
+                return Some(Chain::from_indices(self.adapters, &self.path));
+            }
+
+            let edges = self.adapters.edges(node);
+            let cursor = self.cursors.last_mut().unwrap();
+
+            while *cursor < edges.len() && !self.reaches_device[edges[*cursor]] {
+                *cursor += 1;
+            }
+
+            if let Some(&edge) = edges.get(*cursor) {
+                *cursor += 1;
+                self.path.push(edge);
+                self.cursors.push(0);
+                self.emitted = false;
+            } else {
+                self.path.pop();
+                self.cursors.pop();
+            }
+        }
+
+        None
+    }
+}
+
+pub fn first_chain(adapters: &Adapters) -> Option<Chain> {
+    Chains::new(adapters).next()
+}
+
+// Of the chains with the fewest adapters, the lexicographically first.
+pub fn shortest_chain(adapters: &Adapters) -> Option<Chain> {
+    extreme_chain(adapters, |candidate, best| candidate < best)
+}
+
+// Of the chains with the most adapters, the lexicographically first.
+pub fn longest_chain(adapters: &Adapters) -> Option<Chain> {
+    extreme_chain(adapters, |candidate, best| candidate > best)
+}
+
+fn extreme_chain<F>(adapters: &Adapters, better: F) -> Option<Chain>
+where F: Fn(usize, usize) -> bool {
+    let device = adapters.device();
+    let mut steps: Vec<Option<usize>> = vec![None; adapters.len()];
+    steps[device] = Some(0);
+
+    for i in (0..device).rev() {
+        for edge in adapters.edges(i) {
+            if let Some(edge_steps) = steps[*edge] {
+                let candidate = edge_steps + 1;
+                if steps[i].is_none_or(|best| better(candidate, best)) {
+                    steps[i] = Some(candidate);
+                }
+            }
+        }
+    }
+
+    steps[0]?;
+
+    let mut path = vec![0];
+    let mut node = 0;
+    while node != device {
+        let remaining = steps[node].unwrap() - 1;
+
+        node = *adapters.edges(node).iter()
+            .find(|edge| steps[**edge] == Some(remaining))
+            .unwrap();
+        path.push(node);
+    }
+
+    Some(Chain::from_indices(adapters, &path))
+}
+
+// Picks uniformly among all chains by weighting each step with the number of
+// chains that continue through it.
+pub fn random_chain<R: Rng>(adapters: &Adapters, rng: &mut R) -> Result<Option<Chain>, ArrangementError> {
+    let counts = adapters.arrangement_counts()?;
+    if counts[0] == 0 {
+        return Ok(None);
+    }
+
+    let device = adapters.device();
+    let mut path = vec![0];
+    let mut node = 0;
+    while node != device {
+        let mut choice = rng.gen_range(0..counts[node]);
+
+        for edge in adapters.edges(node) {
+            if choice < counts[*edge] {
+                node = *edge;
+                break;
+            }
+
+            choice -= counts[*edge];
+        }
+
+        path.push(node);
+    }
+
+    Ok(Some(Chain::from_indices(adapters, &path)))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Selection {
+    First,
+    Shortest,
+    Longest,
+    Random
+}
+
+impl FromStr for Selection {
+    type Err = ParseSelectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first" => Ok(Selection::First),
+            "shortest" => Ok(Selection::Shortest),
+            "longest" => Ok(Selection::Longest),
+            "random" => Ok(Selection::Random),
+            _ => Err(ParseSelectionError { selection: String::from(s) })
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseSelectionError {
+    selection: String
+}
+
+impl Display for ParseSelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "unknown chain selection '{}', expected first, shortest, longest or random", self.selection)
+    }
+}
+
+impl Error for ParseSelectionError { }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::adapters::{DEFAULT_MAX_GAP, DEFAULT_MIN_GAP};
+
+    const SMALL_EXAMPLE: [usize; 11] = [16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
+
+    fn small_example() -> Adapters {
+        Adapters::new(&SMALL_EXAMPLE, DEFAULT_MIN_GAP, DEFAULT_MAX_GAP).unwrap()
+    }
+
+    #[test]
+    fn enumerates_every_chain_in_order() {
+        let adapters = small_example();
+        let chains: Vec<Chain> = Chains::new(&adapters).collect();
+
+        assert_eq!(8, chains.len());
+        assert_eq!(8, chains.iter().map(|chain| chain.joltages().to_vec()).collect::<HashSet<_>>().len());
+        assert!(chains.windows(2).all(|pair| pair[0].joltages() < pair[1].joltages()));
+        assert_eq!("0 -> 1 -> 4 -> 7 -> 10 -> 12 -> 15 -> 16 -> 19 -> 22", chains.last().unwrap().to_string());
+    }
+
+    #[test]
+    fn enumeration_is_lazy() {
+        let joltages: Vec<usize> = (1..=300).collect();
+        let adapters = Adapters::new(&joltages, DEFAULT_MIN_GAP, DEFAULT_MAX_GAP).unwrap();
+
+        assert_eq!(3, Chains::new(&adapters).take(3).count());
+    }
+
+    #[test]
+    fn dead_ends_are_skipped() {
+        // With 1-jolt steps refused, 2 can't go anywhere.
+        let adapters = Adapters::new(&[2, 3, 6], 2, DEFAULT_MAX_GAP).unwrap();
+        let chains: Vec<String> = Chains::new(&adapters).map(|chain| chain.to_string()).collect();
+
+        assert_eq!(vec!["0 -> 3 -> 6 -> 9"], chains);
+        assert_eq!(None, Chains::new(&Adapters::new(&[1, 10], 1, 3).unwrap()).next());
+    }
+
+    #[test]
+    fn first_chain_uses_every_adapter() {
+        let chain = first_chain(&small_example()).unwrap();
+
+        assert_eq!(11, chain.adapter_count());
+        assert_eq!(Some(&7), chain.differences().get(&1));
+        assert_eq!(Some(&5), chain.differences().get(&3));
+    }
+
+    #[test]
+    fn shortest_and_longest_chains() {
+        let adapters = small_example();
+
+        assert_eq!("0 -> 1 -> 4 -> 7 -> 10 -> 12 -> 15 -> 16 -> 19 -> 22", shortest_chain(&adapters).unwrap().to_string());
+        assert_eq!(first_chain(&adapters), longest_chain(&adapters));
+    }
+
+    #[test]
+    fn random_chains_are_valid_and_cover_every_chain() {
+        let adapters = small_example();
+        let all: HashSet<Vec<usize>> = Chains::new(&adapters).map(|chain| chain.joltages().to_vec()).collect();
+        let mut rng = StdRng::seed_from_u64(10);
+        let mut seen = HashSet::new();
+
+        for _ in 0..200 {
+            let chain = random_chain(&adapters, &mut rng).unwrap().unwrap();
+            assert!(all.contains(chain.joltages()));
+            seen.insert(chain.joltages().to_vec());
+        }
+
+        assert_eq!(all, seen);
+    }
+}
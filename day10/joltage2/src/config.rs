@@ -1,8 +1,21 @@
 use std::error::Error;
 use std::fmt::Display;
 
+use crate::adapters::{DEFAULT_MAX_GAP, DEFAULT_MIN_GAP};
+use crate::chains::{ParseSelectionError, Selection};
+
 pub struct Config {
-    pub filename: String
+    pub filename: String,
+    pub min_gap: usize,
+    pub max_gap: usize,
+    pub command: Command,
+    pub seed: Option<u64>
+}
+
+pub enum Command {
+    CountArrangements,
+    Chain(Selection),
+    List(usize)
 }
 
 impl Config {
@@ -12,22 +25,52 @@ impl Config {
         }
 
         let filename = args[1].clone();
-        
-        Ok(Config { filename })
+        let mut min_gap = DEFAULT_MIN_GAP;
+        let mut max_gap = DEFAULT_MAX_GAP;
+        let mut command = Command::CountArrangements;
+        let mut seed = None;
+
+        let mut options = args[2..].iter();
+        while let Some(option) = options.next() {
+            let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+
+            match option.as_str() {
+                "--min-gap" => { min_gap = value.parse().map_err(|_| ParseConfigError::InvalidGap)?; },
+                "--max-gap" => { max_gap = value.parse().map_err(|_| ParseConfigError::InvalidGap)?; },
+                "--chain" => { command = Command::Chain(value.parse().map_err(ParseConfigError::InvalidSelection)?); },
+                "--list" => { command = Command::List(value.parse().map_err(|_| ParseConfigError::InvalidNumber)?); },
+                "--seed" => { seed = Some(value.parse().map_err(|_| ParseConfigError::InvalidNumber)?); },
+                _ => { return Err(ParseConfigError::UnknownOption(option.clone())); }
+            }
+        }
+
+        if max_gap == 0 || min_gap > max_gap {
+            return Err(ParseConfigError::InvalidGap);
+        }
+
+        Ok(Config { filename, min_gap, max_gap, command, seed })
     }
 }
 
 #[derive(Debug)]
 pub enum ParseConfigError {
-    NotEnoughArguments
+    NotEnoughArguments,
+    UnknownOption(String),
+    InvalidGap,
+    InvalidSelection(ParseSelectionError),
+    InvalidNumber
 }
 
 impl Display for ParseConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            ParseConfigError::NotEnoughArguments => "not enough arguments"
-        })
+        match self {
+            ParseConfigError::NotEnoughArguments => writeln!(f, "not enough arguments"),
+            ParseConfigError::UnknownOption(option) => writeln!(f, "unknown option '{}'", option),
+            ParseConfigError::InvalidGap => writeln!(f, "gaps must be numbers with 0 < max gap and min gap <= max gap"),
+            ParseConfigError::InvalidSelection(error) => write!(f, "{}", error),
+            ParseConfigError::InvalidNumber => writeln!(f, "couldn't parse number")
+        }
     }
 }
 
-impl Error for ParseConfigError { }
\ No newline at end of file
+impl Error for ParseConfigError { }
@@ -1,103 +1,95 @@
-use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::Display;
 use std::fs::{File};
 use std::io::{self, BufRead};
 use std::path::Path;
 
-use config::Config;
+use rand::{SeedableRng, rngs::StdRng};
 
-pub mod config;
+use adapters::Adapters;
+use chains::{Chain, Chains, Selection};
+use config::{Command, Config};
 
-pub fn run(config: Config) -> Result<u32, Box<dyn Error>> {
-    let filename = config.filename;
+pub mod adapters;
+pub mod chains;
+pub mod config;
 
-    let mut adapters = Vec::new();
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
+    let adapters = read_adapters(&config)?;
 
-    adapters.push(0); // outlet with effective joltage of 0
+    match config.command {
+        Command::CountArrangements => Ok(adapters.count_arrangements()?.to_string()),
+        Command::Chain(selection) => {
+            let chain = select_chain(&adapters, selection, config.seed)?
+                .ok_or(ApplicationError::NoChain)?;
 
-    let lines = read_lines(filename)?;
-    for line in lines {
-        let adapter = line?.parse::<usize>()?;
-        adapters.push(adapter);
-    }
+            Ok(describe_chain(&chain))
+        },
+        Command::List(count) => {
+            let chains: Vec<String> = Chains::new(&adapters)
+                .take(count)
+                .map(|chain| chain.to_string())
+                .collect();
 
-    adapters.sort();
-
-    adapters.push(adapters.last().unwrap() + 3); // device with joltage of 3 higher than highest adapter
-
-    let mut edges = HashMap::new();
-    
-    for i in 0..adapters.len() {
-        let adapter = adapters[i];
-        let mut edges_for_adapter = Vec::new();
-
-        for look_forward in i+1..=i+3 {
-            if let Some(forward) = adapters.get(look_forward) {
-                if forward - adapter <= 3 {
-                    edges_for_adapter.push(*forward);
-                } else {
-                    break;
-                }
-            } else {
-                break;
-            }
-        }
-
-        if edges_for_adapter.len() > 0 {
-            edges.insert(adapter, edges_for_adapter);
+            Ok(chains.join("\n"))
         }
     }
-
-    println!("{:?}", count_paths(0, &edges));
-
-    Ok(0)
 }
 
-fn count_paths(adapter: usize, edges: &HashMap<usize, Vec<usize>>) -> usize {
-    let mut memo = HashMap::new();
-
-    count_paths_helper(adapter, edges, &mut memo)
+fn select_chain(adapters: &Adapters, selection: Selection, seed: Option<u64>) -> Result<Option<Chain>, Box<dyn Error>> {
+    Ok(match selection {
+        Selection::First => chains::first_chain(adapters),
+        Selection::Shortest => chains::shortest_chain(adapters),
+        Selection::Longest => chains::longest_chain(adapters),
+        Selection::Random => {
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy()
+            };
+
+            chains::random_chain(adapters, &mut rng)?
+        }
+    })
 }
 
-fn count_paths_helper(adapter: usize, edges: &HashMap<usize, Vec<usize>>, memo: &mut HashMap<usize, usize>) -> usize {
-    if let Some(memoized_answer) = memo.get(&adapter) {
-        return *memoized_answer;
-    }
+fn describe_chain(chain: &Chain) -> String {
+    let mut lines = vec![chain.to_string(), format!("adapters: {}", chain.adapter_count())];
 
-    if let Some(edges_for_adapter) = edges.get(&adapter) {
-        let mut accum = 0;
-        for edge in edges_for_adapter {
-            accum = accum + count_paths_helper(*edge, edges, memo)
-        }
+    for (difference, count) in chain.differences() {
+        lines.push(format!("{}-jolt differences: {}", difference, count));
+    }
 
-        memo.insert(adapter, accum);
+    lines.join("\n")
+}
 
-        accum
-    } else {
-        memo.insert(adapter, 1);
+fn read_adapters(config: &Config) -> Result<Adapters, Box<dyn Error>> {
+    let mut joltages = Vec::new();
 
-        1
+    let lines = read_lines(&config.filename)?;
+    for line in lines {
+        joltages.push(line?.parse::<usize>()?);
     }
-}
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    Ok(Adapters::new(&joltages, config.min_gap, config.max_gap)?)
 }
 
 #[derive(Debug)]
 pub enum ApplicationError {
-    AnError
+    NoChain
 }
 
 impl Display for ApplicationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", match self {
-            AnError => "an error occurred"  
+            ApplicationError::NoChain => "no chain of adapters reaches the device"
         })
     }
 }
 
 impl Error for ApplicationError { }
+
+fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+where P: AsRef<Path>, {
+    let file = File::open(filename)?;
+    Ok(io::BufReader::new(file).lines())
+}
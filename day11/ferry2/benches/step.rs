@@ -0,0 +1,62 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use ferry::rules::Rules;
+use ferry::seating::{SeatingChart, SeatingChartRow};
+
+const SIZE: usize = 1000;
+
+// A deterministic chart where roughly floor_tenths out of every ten cells are
+// floor, so line-of-sight scans have something to look past.
+fn rows(size: usize, floor_tenths: u64) -> Vec<SeatingChartRow> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+
+    (0..size)
+        .map(|_| {
+            let row: String = (0..size)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+
+                    if state % 10 < floor_tenths { '.' } else { 'L' }
+                })
+                .collect();
+
+            row.parse().unwrap()
+        })
+        .collect()
+}
+
+fn step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("line_of_sight_1000x1000");
+    group.sample_size(10);
+
+    for (density, floor_tenths) in [("dense", 3), ("sparse", 8)].iter() {
+        group.bench_function(BenchmarkId::new("build", density), |b| {
+            b.iter(|| SeatingChart::new(rows(SIZE, *floor_tenths), Rules::part_two()).unwrap())
+        });
+
+        let mut precomputed = SeatingChart::new(rows(SIZE, *floor_tenths), Rules::part_two()).unwrap();
+        group.bench_function(BenchmarkId::new("step_precomputed", density), |b| {
+            b.iter(|| precomputed.step_sequential())
+        });
+
+        #[cfg(feature = "parallel")]
+        {
+            let mut parallel = SeatingChart::new(rows(SIZE, *floor_tenths), Rules::part_two()).unwrap();
+            group.bench_function(BenchmarkId::new("step_parallel", density), |b| {
+                b.iter(|| parallel.step_parallel())
+            });
+        }
+
+        let mut scanning = SeatingChart::new(rows(SIZE, *floor_tenths), Rules::part_two()).unwrap();
+        group.bench_function(BenchmarkId::new("step_scanning", density), |b| {
+            b.iter(|| scanning.step_scanning())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
@@ -1,33 +1,92 @@
 use std::error::Error;
 use std::fmt::Display;
 
+use crate::rules::{ParseRulesError, Rules};
+
 pub struct Config {
-    pub filename: String
+    pub filename: String,
+    pub rules: Rules,
+    pub verbose: bool,
+    pub limit: Option<usize>,
+    pub frames: Option<String>,
+    pub gif: Option<String>,
+    pub scale: usize,
+    pub delay: u16
 }
 
 impl Config {
+    // ferry <file> [--rules part1|part2] [--neighborhood adjacent|sight|radius:N]
+    //     [--birth N] [--death N] [--verbose] [--limit N]
+    //     [--frames DIR] [--gif FILE [--scale N] [--delay CENTISECONDS]]
+    //
+    // --rules picks a starting preset (part2 by default) and the other options
+    // override individual parts of it, whatever order they're given in.
     pub fn new(args: &[String]) -> Result<Config, ParseConfigError> {
         if args.len() < 2 {
             return Err(ParseConfigError::NotEnoughArguments);
         }
 
         let filename = args[1].clone();
-        
-        Ok(Config { filename })
+        let mut rules = Rules::part_two();
+        let mut neighborhood = None;
+        let mut birth = None;
+        let mut death = None;
+        let mut verbose = false;
+        let mut limit = None;
+        let mut frames = None;
+        let mut gif = None;
+        let mut scale = 4;
+        let mut delay = 10;
+
+        let mut options = args[2..].iter();
+        while let Some(option) = options.next() {
+            if option == "--verbose" {
+                verbose = true;
+                continue;
+            }
+
+            let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+            match option.as_str() {
+                "--rules" => { rules = value.parse().map_err(ParseConfigError::InvalidRules)?; },
+                "--neighborhood" => { neighborhood = Some(value.parse().map_err(ParseConfigError::InvalidRules)?); },
+                "--birth" => { birth = Some(value.parse().map_err(|_| ParseConfigError::InvalidThreshold)?); },
+                "--death" => { death = Some(value.parse().map_err(|_| ParseConfigError::InvalidThreshold)?); },
+                "--limit" => { limit = Some(value.parse().map_err(|_| ParseConfigError::InvalidNumber)?); },
+                "--frames" => { frames = Some(value.clone()); },
+                "--gif" => { gif = Some(value.clone()); },
+                "--scale" => { scale = value.parse().map_err(|_| ParseConfigError::InvalidNumber)?; },
+                "--delay" => { delay = value.parse().map_err(|_| ParseConfigError::InvalidNumber)?; },
+                _ => { return Err(ParseConfigError::UnknownOption(option.clone())); }
+            }
+        }
+
+        rules.neighborhood = neighborhood.unwrap_or(rules.neighborhood);
+        rules.birth = birth.unwrap_or(rules.birth);
+        rules.death = death.unwrap_or(rules.death);
+
+        Ok(Config { filename, rules, verbose, limit, frames, gif, scale, delay })
     }
 }
 
 #[derive(Debug)]
 pub enum ParseConfigError {
-    NotEnoughArguments
+    NotEnoughArguments,
+    UnknownOption(String),
+    InvalidRules(ParseRulesError),
+    InvalidThreshold,
+    InvalidNumber
 }
 
 impl Display for ParseConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            ParseConfigError::NotEnoughArguments => "not enough arguments"
-        })
+        match self {
+            ParseConfigError::NotEnoughArguments => writeln!(f, "not enough arguments"),
+            ParseConfigError::UnknownOption(option) => writeln!(f, "unknown option '{}'", option),
+            ParseConfigError::InvalidRules(error) => write!(f, "{}", error),
+            ParseConfigError::InvalidThreshold => writeln!(f, "couldn't parse threshold"),
+            ParseConfigError::InvalidNumber => writeln!(f, "couldn't parse number")
+        }
     }
 }
 
-impl Error for ParseConfigError { }
\ No newline at end of file
+impl Error for ParseConfigError { }
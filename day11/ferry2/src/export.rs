@@ -0,0 +1,171 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::seating::{SeatingChart, SeatingChartStatus};
+
+// Floor, empty seat, occupied seat.
+const PALETTE: [u8; 9] = [
+    0x20, 0x20, 0x20,
+    0x3c, 0xb0, 0x4a,
+    0xd6, 0x3a, 0x3a
+];
+
+pub trait FrameWriter {
+    fn write_frame(&mut self, generation: usize, chart: &SeatingChart) -> Result<(), Box<dyn Error>>;
+}
+
+// Writes each generation to its own text file, generation-0000.txt and so on,
+// in the same L/#/. layout the charts are read in.
+pub struct TextFrames {
+    directory: PathBuf
+}
+
+impl TextFrames {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Result<TextFrames, Box<dyn Error>> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+
+        Ok(TextFrames { directory })
+    }
+}
+
+impl FrameWriter for TextFrames {
+    fn write_frame(&mut self, generation: usize, chart: &SeatingChart) -> Result<(), Box<dyn Error>> {
+        let path = self.directory.join(format!("generation-{:04}.txt", generation));
+        fs::write(path, format!("{}\n", chart))?;
+
+        Ok(())
+    }
+}
+
+// Writes every generation as one frame of a looping animated GIF, with each
+// seat drawn as a scale x scale block.
+pub struct GifFrames<W: Write> {
+    encoder: Encoder<W>,
+    width: u16,
+    height: u16,
+    scale: usize,
+    delay: u16
+}
+
+impl<W: Write> GifFrames<W> {
+    // delay is in hundredths of a second per frame.
+    pub fn new(writer: W, chart: &SeatingChart, scale: usize, delay: u16) -> Result<GifFrames<W>, Box<dyn Error>> {
+        let width = image_dimension(chart.width(), scale)?;
+        let height = image_dimension(chart.height(), scale)?;
+
+        let mut encoder = Encoder::new(writer, width, height, &PALETTE)?;
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        Ok(GifFrames { encoder, width, height, scale, delay })
+    }
+}
+
+impl<W: Write> FrameWriter for GifFrames<W> {
+    fn write_frame(&mut self, _generation: usize, chart: &SeatingChart) -> Result<(), Box<dyn Error>> {
+        let mut pixels = Vec::with_capacity(self.width as usize * self.height as usize);
+
+        for row in chart.rows() {
+            let mut line = Vec::with_capacity(self.width as usize);
+            for seat in row.seats() {
+                let index = match seat {
+                    SeatingChartStatus::Floor => 0,
+                    SeatingChartStatus::Unoccupied => 1,
+                    SeatingChartStatus::Occupied => 2
+                };
+
+                line.extend(std::iter::repeat_n(index, self.scale));
+            }
+
+            for _ in 0..self.scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+
+        let mut frame = Frame::from_indexed_pixels(self.width, self.height, pixels, None);
+        frame.delay = self.delay;
+
+        self.encoder.write_frame(&frame)?;
+
+        Ok(())
+    }
+}
+
+fn image_dimension(cells: usize, scale: usize) -> Result<u16, ExportError> {
+    cells.checked_mul(scale)
+        .filter(|size| *size > 0 && *size <= u16::MAX as usize)
+        .map(|size| size as u16)
+        .ok_or(ExportError::ImageTooLarge)
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    ImageTooLarge
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            ExportError::ImageTooLarge => "chart is too large for a GIF at this scale"
+        })
+    }
+}
+
+impl Error for ExportError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::{Outcome, simulate};
+    use crate::rules::{Neighborhood, Rules};
+
+    fn chart(input: &str, rules: Rules) -> SeatingChart {
+        let rows = input.lines().map(|line| line.parse().unwrap()).collect();
+
+        SeatingChart::new(rows, rules).unwrap()
+    }
+
+    #[test]
+    fn text_frames_are_written_per_generation() {
+        let directory = std::env::temp_dir().join(format!("ferry-frames-{}", std::process::id()));
+        let mut chart = chart("L.L\n...", Rules::new(Neighborhood::Adjacent, 0, 0));
+        let mut writers: Vec<Box<dyn FrameWriter>> = vec![Box::new(TextFrames::new(&directory).unwrap())];
+
+        let (_, outcome) = simulate(&mut chart, None, &mut writers).unwrap();
+
+        assert_eq!(Outcome::Cycle { start: 0, period: 2 }, outcome);
+        assert_eq!("L.L\n...\n", fs::read_to_string(directory.join("generation-0000.txt")).unwrap());
+        assert_eq!("#.#\n...\n", fs::read_to_string(directory.join("generation-0001.txt")).unwrap());
+        assert!(!directory.join("generation-0002.txt").exists());
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn gif_frames_are_encoded() {
+        let mut chart = chart("L.L\n#..", Rules::part_one());
+        let mut buffer = Vec::new();
+
+        {
+            let mut gif = GifFrames::new(&mut buffer, &chart, 2, 10).unwrap();
+            gif.write_frame(0, &chart).unwrap();
+            chart.step();
+            gif.write_frame(1, &chart).unwrap();
+        }
+
+        assert!(buffer.starts_with(b"GIF89a"));
+        assert_eq!(&[6, 0, 4, 0], &buffer[6..10]);
+    }
+
+    #[test]
+    fn oversized_gif_is_rejected() {
+        let chart = chart("L.L", Rules::part_one());
+
+        assert!(GifFrames::new(Vec::new(), &chart, 30000, 10).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::export::FrameWriter;
+use crate::seating::SeatingChart;
+
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    // The chart stopped changing at this generation.
+    Stable { generation: usize },
+    // From generation `start` on, the chart repeats every `period` generations.
+    Cycle { start: usize, period: usize },
+    // The generation limit was reached before any state repeated.
+    Unsettled { generations: usize }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Stable { generation } => write!(f, "stable from generation {}", generation),
+            Outcome::Cycle { start, period } => write!(f, "cycle of period {} from generation {}", period, start),
+            Outcome::Unsettled { generations } => write!(f, "no repeat within {} generations", generations)
+        }
+    }
+}
+
+// Every generation seen so far (generation 0 being the initial chart). Each
+// is kept as its hash plus the chart's occupancy: the hash narrows a repeat
+// down to a few candidates, and the occupancy confirms it, so a collision
+// can't end the simulation on a state that never came back.
+pub struct History {
+    hashes: Vec<u64>,
+    occupancies: Vec<Vec<u64>>,
+    change_counts: Vec<usize>,
+    seen: HashMap<u64, Vec<usize>>
+}
+
+impl History {
+    pub fn new(chart: &SeatingChart) -> History {
+        let hash = chart.state_hash();
+        let mut seen = HashMap::new();
+        seen.insert(hash, vec![0]);
+
+        History { hashes: vec![hash], occupancies: vec![chart.occupancy()], change_counts: Vec::new(), seen }
+    }
+
+    // Number of distinct generations recorded, including the initial one.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    pub fn hashes(&self) -> &[u64] {
+        &self.hashes
+    }
+
+    // Seats that changed going into each generation after the first.
+    pub fn change_counts(&self) -> &[usize] {
+        &self.change_counts
+    }
+
+    // Records the chart after another step. Returns how the simulation ends if
+    // this state has been seen before; repeated states aren't recorded. A step
+    // that changed nothing is stable without looking anything up.
+    pub fn record(&mut self, chart: &SeatingChart, change_count: usize) -> Option<Outcome> {
+        let generation = self.hashes.len();
+
+        if change_count == 0 {
+            return Some(Outcome::Stable { generation: generation - 1 });
+        }
+
+        let hash = chart.state_hash();
+        let occupancy = chart.occupancy();
+        let occupancies = &self.occupancies;
+        let candidates = self.seen.entry(hash).or_default();
+
+        if let Some(start) = candidates.iter().find(|start| occupancies[**start] == occupancy) {
+            return Some(Outcome::Cycle { start: *start, period: generation - start });
+        }
+
+        candidates.push(generation);
+        self.hashes.push(hash);
+        self.occupancies.push(occupancy);
+        self.change_counts.push(change_count);
+
+        None
+    }
+}
+
+// Steps the chart until it settles into a fixed state or a cycle, or until
+// `limit` steps have been taken. Every distinct generation is handed to the
+// writers as it's produced.
+pub fn simulate(chart: &mut SeatingChart, limit: Option<usize>, writers: &mut [Box<dyn FrameWriter>]) -> Result<(History, Outcome), Box<dyn Error>> {
+    let mut history = History::new(chart);
+
+    for writer in writers.iter_mut() {
+        writer.write_frame(0, chart)?;
+    }
+
+    loop {
+        if let Some(limit) = limit {
+            if history.len() > limit {
+                let generations = history.len() - 1;
+                return Ok((history, Outcome::Unsettled { generations }));
+            }
+        }
+
+        let change_count = chart.step();
+        if let Some(outcome) = history.record(chart, change_count) {
+            return Ok((history, outcome));
+        }
+
+        let generation = history.len() - 1;
+        for writer in writers.iter_mut() {
+            writer.write_frame(generation, chart)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{Neighborhood, Rules};
+
+    const EXAMPLE: &str = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    fn chart(input: &str, rules: Rules) -> SeatingChart {
+        let rows = input.lines().map(|line| line.parse().unwrap()).collect();
+
+        SeatingChart::new(rows, rules).unwrap()
+    }
+
+    #[test]
+    fn example_becomes_stable() {
+        let mut chart = chart(EXAMPLE, Rules::part_one());
+        let (history, outcome) = simulate(&mut chart, None, &mut []).unwrap();
+
+        assert_eq!(Outcome::Stable { generation: 5 }, outcome);
+        assert_eq!(6, history.len());
+        assert_eq!(37, chart.occupied_count());
+    }
+
+    #[test]
+    fn oscillating_rules_are_detected() {
+        // With a death threshold of 0 every occupied seat empties again.
+        let mut chart = chart("L.L", Rules::new(Neighborhood::Adjacent, 0, 0));
+        let (history, outcome) = simulate(&mut chart, None, &mut []).unwrap();
+
+        assert_eq!(Outcome::Cycle { start: 0, period: 2 }, outcome);
+        assert_eq!(&[2], history.change_counts());
+    }
+
+    #[test]
+    fn hash_collisions_are_not_repeats() {
+        let mut chart = chart("L.L", Rules::new(Neighborhood::Adjacent, 0, 0));
+        let mut history = History::new(&chart);
+
+        // Pretend the next state hashes the same as the initial one.
+        chart.step();
+        history.seen.insert(chart.state_hash(), vec![0]);
+
+        assert_eq!(None, history.record(&chart, 2));
+        assert_eq!(2, history.len());
+    }
+
+    #[test]
+    fn limit_stops_the_simulation() {
+        let mut chart = chart(EXAMPLE, Rules::part_one());
+        let (history, outcome) = simulate(&mut chart, Some(2), &mut []).unwrap();
+
+        assert_eq!(Outcome::Unsettled { generations: 2 }, outcome);
+        assert_eq!(3, history.len());
+    }
+}
@@ -1,40 +1,59 @@
 use std::error::Error;
-use std::fmt::Display;
 use std::fs::{File};
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, BufWriter};
 use std::path::Path;
 
 use config::Config;
+use export::{FrameWriter, GifFrames, TextFrames};
+use history::simulate;
 use seating::{SeatingChart, SeatingChartRow};
 
 pub mod config;
-mod seating;
-
-pub fn run(config: Config) -> Result<usize, Box<dyn Error>> {
-    let filename = config.filename;
+pub mod export;
+pub mod history;
+pub mod neighbors;
+pub mod rules;
+pub mod seating;
+
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
+    let mut chart = read_chart(&config)?;
+
+    let mut writers: Vec<Box<dyn FrameWriter>> = Vec::new();
+    if config.verbose {
+        writers.push(Box::new(PrintFrames));
+    }
+    if let Some(directory) = &config.frames {
+        writers.push(Box::new(TextFrames::new(directory)?));
+    }
+    if let Some(filename) = &config.gif {
+        let file = BufWriter::new(File::create(filename)?);
+        writers.push(Box::new(GifFrames::new(file, &chart, config.scale, config.delay)?));
+    }
 
-    let mut rows = Vec::new();
+    let (_, outcome) = simulate(&mut chart, config.limit, &mut writers)?;
 
-    let lines = read_lines(filename)?;
-    for line in lines {
-        rows.push(line?.parse::<SeatingChartRow>()?);
-    }
+    Ok(format!("{}\n{}", outcome, chart.occupied_count()))
+}
 
-    let mut chart = SeatingChart::new(rows)?;
-    println!("initial:\n{}", chart);
+struct PrintFrames;
 
-    for step in 1.. {
-        let change_count = chart.step();
-        println!("step {}, {} changes:\n{}", step, change_count, chart);
+impl FrameWriter for PrintFrames {
+    fn write_frame(&mut self, generation: usize, chart: &SeatingChart) -> Result<(), Box<dyn Error>> {
+        println!("generation {}:\n{}", generation, chart);
 
-        if change_count == 0 {
-            break;
-        }
+        Ok(())
     }
+}
 
-    let occupied_count = chart.occupied_count();
+fn read_chart(config: &Config) -> Result<SeatingChart, Box<dyn Error>> {
+    let mut rows = Vec::new();
 
-    Ok(occupied_count)
+    let lines = read_lines(&config.filename)?;
+    for line in lines {
+        rows.push(line?.parse::<SeatingChartRow>()?);
+    }
+
+    Ok(SeatingChart::new(rows, config.rules)?)
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -42,18 +61,3 @@ where P: AsRef<Path>, {
     let file = File::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
-
-#[derive(Debug)]
-pub enum ApplicationError {
-    AnError
-}
-
-impl Display for ApplicationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            AnError => "an error occurred"  
-        })
-    }
-}
-
-impl Error for ApplicationError { }
@@ -0,0 +1,111 @@
+use std::convert::TryFrom;
+
+use crate::seating::{SeatingChartRow, SeatingChartStatus};
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1, 0), (1, 0),
+    (-1, 1), (0, 1), (1, 1)
+];
+
+const NONE: u32 = u32::MAX;
+
+pub const MAX_CELLS: usize = NONE as usize;
+
+// The seats every seat can see, worked out once per chart. Floor never turns
+// into a seat, so which seats a seat can see is fixed no matter how the
+// occupancy changes from step to step. Only line of sight needs this: the
+// other neighborhoods are a window around the seat that's cheap to count
+// directly, and listing them up front would grow with the radius squared.
+//
+// Cells are numbered row by row (y * width + x), and each cell's neighbors sit
+// in seats[offsets[cell]..offsets[cell + 1]]. Floor cells have none. Charts
+// are limited to MAX_CELLS cells so every cell fits in a u32, leaving
+// u32::MAX itself free to mean no seat.
+#[derive(Debug)]
+pub struct Neighbors {
+    width: usize,
+    offsets: Vec<usize>,
+    seats: Vec<u32>
+}
+
+impl Neighbors {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn of(&self, x: usize, y: usize) -> &[u32] {
+        let cell = y * self.width + x;
+
+        &self.seats[self.offsets[cell]..self.offsets[cell + 1]]
+    }
+
+    // For each direction, the first seat seen from a cell is either the cell
+    // next to it (if that's a seat) or whatever that cell sees in the same
+    // direction. Visiting cells against the direction of travel means the
+    // next cell is always answered first, so each direction is one pass.
+    pub fn line_of_sight(rows: &[SeatingChartRow]) -> Neighbors {
+        let (width, height) = dimensions(rows);
+        let mut visible = vec![[NONE; 8]; width * height];
+
+        for (direction, (x_dir, y_dir)) in DIRECTIONS.iter().enumerate() {
+            for y in ordered(height, *y_dir) {
+                for x in ordered(width, *x_dir) {
+                    let neighbor_x = x as isize + x_dir;
+                    let neighbor_y = y as isize + y_dir;
+
+                    if neighbor_x < 0 || neighbor_y < 0 || neighbor_x as usize >= width || neighbor_y as usize >= height {
+                        continue;
+                    }
+
+                    let (neighbor_x, neighbor_y) = (neighbor_x as usize, neighbor_y as usize);
+                    let neighbor = cell_index(width, neighbor_x, neighbor_y);
+
+                    visible[y * width + x][direction] = if is_seat(rows, neighbor_x, neighbor_y) {
+                        neighbor
+                    } else {
+                        visible[neighbor as usize][direction]
+                    };
+                }
+            }
+        }
+
+        let mut offsets = Vec::with_capacity(width * height + 1);
+        let mut seats = Vec::new();
+
+        offsets.push(0);
+
+        for y in 0..height {
+            for x in 0..width {
+                if is_seat(rows, x, y) {
+                    seats.extend(visible[y * width + x].iter().filter(|seat| **seat != NONE));
+                }
+
+                offsets.push(seats.len());
+            }
+        }
+
+        Neighbors { width, offsets, seats }
+    }
+}
+
+fn dimensions(rows: &[SeatingChartRow]) -> (usize, usize) {
+    (rows.first().map_or(0, |row| row.len()), rows.len())
+}
+
+fn is_seat(rows: &[SeatingChartRow], x: usize, y: usize) -> bool {
+    rows[y].seats()[x] != SeatingChartStatus::Floor
+}
+
+fn cell_index(width: usize, x: usize, y: usize) -> u32 {
+    u32::try_from(y * width + x).expect("chart has more than u32::MAX cells")
+}
+
+// 0..len, reversed when moving in the positive direction.
+fn ordered(len: usize, direction: isize) -> Box<dyn Iterator<Item = usize>> {
+    if direction > 0 {
+        Box::new((0..len).rev())
+    } else {
+        Box::new(0..len)
+    }
+}
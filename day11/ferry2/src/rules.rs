@@ -0,0 +1,95 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+// Which seats count as a seat's neighbors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Neighborhood {
+    // The eight seats immediately around it.
+    Adjacent,
+    // The first seat visible in each of the eight directions, looking past floor.
+    LineOfSight,
+    // Every seat within the given number of rows and columns.
+    Radius(usize)
+}
+
+impl FromStr for Neighborhood {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "adjacent" => Ok(Neighborhood::Adjacent),
+            "sight" => Ok(Neighborhood::LineOfSight),
+            _ => {
+                let radius = s.strip_prefix("radius:")
+                    .and_then(|radius| radius.parse::<usize>().ok())
+                    .filter(|radius| *radius > 0)
+                    .ok_or_else(|| ParseRulesError::UnknownNeighborhood(String::from(s)))?;
+
+                Ok(Neighborhood::Radius(radius))
+            }
+        }
+    }
+}
+
+impl Display for Neighborhood {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Neighborhood::Adjacent => write!(f, "adjacent"),
+            Neighborhood::LineOfSight => write!(f, "sight"),
+            Neighborhood::Radius(radius) => write!(f, "radius:{}", radius)
+        }
+    }
+}
+
+// An empty seat becomes occupied when at most `birth` of its neighbors are
+// occupied, and an occupied seat empties when `death` or more are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rules {
+    pub neighborhood: Neighborhood,
+    pub birth: usize,
+    pub death: usize
+}
+
+impl Rules {
+    pub fn new(neighborhood: Neighborhood, birth: usize, death: usize) -> Rules {
+        Rules { neighborhood, birth, death }
+    }
+
+    pub fn part_one() -> Rules {
+        Rules::new(Neighborhood::Adjacent, 0, 4)
+    }
+
+    pub fn part_two() -> Rules {
+        Rules::new(Neighborhood::LineOfSight, 0, 5)
+    }
+}
+
+impl FromStr for Rules {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "part1" => Ok(Rules::part_one()),
+            "part2" => Ok(Rules::part_two()),
+            _ => Err(ParseRulesError::UnknownPreset(String::from(s)))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseRulesError {
+    UnknownNeighborhood(String),
+    UnknownPreset(String)
+}
+
+impl Display for ParseRulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseRulesError::UnknownNeighborhood(s) => writeln!(f, "unknown neighborhood '{}', expected adjacent, sight or radius:N", s),
+            ParseRulesError::UnknownPreset(s) => writeln!(f, "unknown rules '{}', expected part1 or part2", s)
+        }
+    }
+}
+
+impl Error for ParseRulesError { }
@@ -1,18 +1,29 @@
+use std::collections::hash_map::DefaultHasher;
 use std::error::Error;
 use std::fmt::Display;
-use std::iter;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
-use std::time::UNIX_EPOCH;
+
+use crate::neighbors::{self, Neighbors};
+use crate::rules::{Neighborhood, Rules};
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1, 0), (1, 0),
+    (-1, 1), (0, 1), (1, 1)
+];
 
 #[derive(Debug)]
 pub struct SeatingChart {
     rows: Vec<SeatingChartRow>,
-    swap: Vec<SeatingChartRow>
+    swap: Vec<SeatingChartRow>,
+    rules: Rules,
+    neighbors: Option<Neighbors>
 }
 
 impl SeatingChart {
-    pub fn new(rows: Vec<SeatingChartRow>) -> Result<SeatingChart, CreateSeatingChartError> {
-        let first_row = rows.get(0)
+    pub fn new(rows: Vec<SeatingChartRow>, rules: Rules) -> Result<SeatingChart, CreateSeatingChartError> {
+        let first_row = rows.first()
             .ok_or(CreateSeatingChartError::ArgumentEmpty)?;
 
         let chart_width = first_row.len();
@@ -22,76 +33,178 @@ impl SeatingChart {
             }
         }
 
+        if chart_width.checked_mul(rows.len()).is_none_or(|cells| cells > neighbors::MAX_CELLS) {
+            return Err(CreateSeatingChartError::TooLarge);
+        }
+
         let mut swap = Vec::new();
-        for i in 0..rows.len() {
-            let row = iter::repeat(SeatingChartStatus::Floor)
-                .take(chart_width)
-                .collect::<Vec<_>>();
+        for _ in 0..rows.len() {
+            let row = vec![SeatingChartStatus::Floor; chart_width];
 
             swap.push(SeatingChartRow { seats: row });
         }
 
-        Ok(SeatingChart { rows, swap })
+        let neighbors = match rules.neighborhood {
+            Neighborhood::LineOfSight => Some(Neighbors::line_of_sight(&rows)),
+            _ => None
+        };
+
+        Ok(SeatingChart { rows, swap, rules, neighbors })
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
     }
 
-    // If a seat is empty (L) and there are no occupied seats adjacent to it, the seat becomes occupied.
-    // If a seat is occupied (#) and four or more seats adjacent to it are also occupied, the seat becomes empty.
+    // If a seat is empty (L) and no more than `birth` of its neighbors are occupied, the seat becomes occupied.
+    // If a seat is occupied (#) and `death` or more of its neighbors are occupied, the seat becomes empty.
     // Otherwise, the seat's state does not change.
+    //
+    // Rows are worked on in parallel when the `parallel` feature is enabled.
     pub fn step(&mut self) -> usize {
-        let mut change_count = 0;
+        #[cfg(feature = "parallel")]
+        return self.step_parallel();
 
-        for y in 0..self.rows.len() {
-            let row = &self.rows[y];
-            
-            for x in 0..row.len() {
-                let seat = &row.seats[x];
-                
-                let nw = self.value_by_direction(x, y, -1, -1);
-                let n  = self.value_by_direction(x, y, 0, -1);
-                let ne  = self.value_by_direction(x, y, 1, -1);
-                let w  = self.value_by_direction(x, y, -1, 0);
-                let e  = self.value_by_direction(x, y, 1, 0);
-                let sw  = self.value_by_direction(x, y, -1, 1);
-                let s  = self.value_by_direction(x, y, 0, 1);
-                let se  = self.value_by_direction(x, y, 1, 1);
-                
-                let occupied_neighbors = nw + n + ne + w + e + sw + s + se;
-
-                let next_status = match seat {
-                    SeatingChartStatus::Unoccupied => {
-                        if occupied_neighbors == 0 {
-                            SeatingChartStatus::Occupied
-                        } else {
-                            SeatingChartStatus::Unoccupied
-                        }
-                    },
-                    SeatingChartStatus::Occupied => {
-                        if occupied_neighbors >= 5 {
-                            SeatingChartStatus::Unoccupied
-                        } else {
-                            SeatingChartStatus::Occupied
-                        }
-                    },
-                    _ => { seat.clone() }
-                };
+        #[cfg(not(feature = "parallel"))]
+        return self.step_sequential();
+    }
 
-                if seat != &next_status {
-                    change_count = change_count + 1;
-                }
+    pub fn step_sequential(&mut self) -> usize {
+        self.advance(|chart, swap| {
+            swap.iter_mut()
+                .enumerate()
+                .map(|(y, next_row)| chart.next_row(y, next_row, SeatingChart::occupied_precomputed_neighbors))
+                .sum()
+        })
+    }
+
+    // Every row of the next generation depends only on the current one, so
+    // rows can be filled in independently.
+    #[cfg(feature = "parallel")]
+    pub fn step_parallel(&mut self) -> usize {
+        use rayon::prelude::*;
+
+        self.advance(|chart, swap| {
+            swap.par_iter_mut()
+                .enumerate()
+                .map(|(y, next_row)| chart.next_row(y, next_row, SeatingChart::occupied_precomputed_neighbors))
+                .sum()
+        })
+    }
+
+    // Same as step, but finds every seat's neighbors by scanning the chart
+    // again. Kept as the reference the precomputed neighbors are checked and
+    // benchmarked against.
+    pub fn step_scanning(&mut self) -> usize {
+        self.advance(|chart, swap| {
+            swap.iter_mut()
+                .enumerate()
+                .map(|(y, next_row)| chart.next_row(y, next_row, SeatingChart::occupied_neighbors))
+                .sum()
+        })
+    }
+
+    // Lets fill write the next generation into the swap rows, then makes it
+    // the current one.
+    fn advance<F>(&mut self, fill: F) -> usize
+    where F: FnOnce(&SeatingChart, &mut [SeatingChartRow]) -> usize {
+        let mut swap = std::mem::take(&mut self.swap);
+        let change_count = fill(self, &mut swap);
+
+        self.swap = std::mem::replace(&mut self.rows, swap);
+
+        change_count
+    }
+
+    // Fills next_row with row y's next generation and returns how many of its
+    // seats changed.
+    fn next_row<F>(&self, y: usize, next_row: &mut SeatingChartRow, occupied_neighbors: F) -> usize
+    where F: Fn(&SeatingChart, usize, usize) -> usize {
+        let mut change_count = 0;
 
-                self.swap[y].seats[x] = next_status;
+        for (x, seat) in self.rows[y].seats.iter().enumerate() {
+            let next_status = match seat {
+                SeatingChartStatus::Unoccupied => {
+                    if occupied_neighbors(self, x, y) <= self.rules.birth {
+                        SeatingChartStatus::Occupied
+                    } else {
+                        SeatingChartStatus::Unoccupied
+                    }
+                },
+                SeatingChartStatus::Occupied => {
+                    if occupied_neighbors(self, x, y) >= self.rules.death {
+                        SeatingChartStatus::Unoccupied
+                    } else {
+                        SeatingChartStatus::Occupied
+                    }
+                },
+                _ => { seat.clone() }
+            };
+
+            if seat != &next_status {
+                change_count += 1;
             }
-        }
 
-        std::mem::swap(&mut self.rows, &mut self.swap);
+            next_row.seats[x] = next_status;
+        }
 
         change_count
     }
 
+    // Line of sight goes through the precomputed neighbors; the windowed
+    // neighborhoods are counted directly.
+    fn occupied_precomputed_neighbors(&self, x: usize, y: usize) -> usize {
+        let neighbors = match &self.neighbors {
+            Some(neighbors) => neighbors,
+            None => return self.occupied_neighbors(x, y)
+        };
+        let width = neighbors.width();
+
+        neighbors.of(x, y).iter()
+            .filter(|cell| {
+                let cell = **cell as usize;
+                self.rows[cell / width].seats[cell % width] == SeatingChartStatus::Occupied
+            })
+            .count()
+    }
+
+    fn occupied_neighbors(&self, x: usize, y: usize) -> usize {
+        match self.rules.neighborhood {
+            Neighborhood::Adjacent => self.occupied_within(x, y, 1),
+            Neighborhood::Radius(radius) => self.occupied_within(x, y, radius),
+            Neighborhood::LineOfSight => DIRECTIONS.iter()
+                .map(|(x_dir, y_dir)| self.value_by_direction(x, y, *x_dir, *y_dir))
+                .sum()
+        }
+    }
+
+    fn occupied_within(&self, x: usize, y: usize, radius: usize) -> usize {
+        let mut count = 0;
+
+        let last_y = y.saturating_add(radius).min(self.rows.len() - 1);
+        let last_x = x.saturating_add(radius).min(self.rows[y].len() - 1);
+
+        for neighbor_y in y.saturating_sub(radius)..=last_y {
+            for neighbor_x in x.saturating_sub(radius)..=last_x {
+                if (neighbor_x, neighbor_y) != (x, y) {
+                    count += self.value_by_coords(neighbor_x, neighbor_y);
+                }
+            }
+        }
+
+        count
+    }
+
     fn get_by_coords(&self, x: usize, y: usize) -> Option<&SeatingChartStatus> {
         self.rows.get(y)?.seats.get(x)
     }
 
+    fn value_by_coords(&self, x: usize, y: usize) -> usize {
+        self.get_by_coords(x, y)
+            .map(|status| if status == &SeatingChartStatus::Occupied { 1 } else { 0 })
+            .unwrap_or(0)
+    }
+
     fn value_by_direction(&self, x: usize, y: usize, x_dir: isize, y_dir: isize) -> usize {
         if (x == 0 && x_dir == -1) || (y == 0 && y_dir == -1) {
             return 0;
@@ -111,12 +224,53 @@ impl SeatingChart {
         }
     }
 
+    pub fn width(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn rows(&self) -> &[SeatingChartRow] {
+        &self.rows
+    }
+
+    // Identifies the current occupancy; equal charts always hash the same.
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        for row in &self.rows {
+            row.seats.hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
+    // One bit per cell, set where the seat is occupied. Floor never changes, so
+    // this tells any two generations of the same chart apart.
+    pub fn occupancy(&self) -> Vec<u64> {
+        let mut bits = Vec::new();
+
+        for (i, seat) in self.rows.iter().flat_map(|row| row.seats.iter()).enumerate() {
+            if i % 64 == 0 {
+                bits.push(0);
+            }
+
+            if seat == &SeatingChartStatus::Occupied {
+                *bits.last_mut().unwrap() |= 1 << (i % 64);
+            }
+        }
+
+        bits
+    }
+
     pub fn occupied_count(&self) -> usize {
         let mut count = 0;
         for row in &self.rows { 
             for seat in &row.seats {
                 if seat == &SeatingChartStatus::Occupied {
-                    count = count + 1;
+                    count += 1;
                 }
             }
         }
@@ -145,14 +299,16 @@ impl Display for SeatingChart {
 #[derive(Debug)]
 pub enum CreateSeatingChartError {
     ArgumentEmpty,
-    UnevenRows
+    UnevenRows,
+    TooLarge
 }
 
 impl Display for CreateSeatingChartError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", match self {
             CreateSeatingChartError::ArgumentEmpty => "argument cannot be empty",
-            CreateSeatingChartError::UnevenRows => "rows must be of equal width"
+            CreateSeatingChartError::UnevenRows => "rows must be of equal width",
+            CreateSeatingChartError::TooLarge => "chart has too many cells"
         })
     }
 }
@@ -185,6 +341,14 @@ impl SeatingChartRow {
     pub fn len(&self) -> usize {
         self.seats.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.seats.is_empty()
+    }
+
+    pub fn seats(&self) -> &[SeatingChartStatus] {
+        &self.seats
+    }
 }
 
 impl Display for SeatingChartRow {
@@ -224,7 +388,7 @@ impl FromStr for SeatingChartRow {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Hash, PartialEq)]
 pub enum SeatingChartStatus {
     Unoccupied,
     Occupied,
@@ -244,4 +408,147 @@ impl Display for ParseSeatingChartRowError {
     }
 }
 
-impl Error for ParseSeatingChartRowError { }
\ No newline at end of file
+impl Error for ParseSeatingChartRowError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+
+    fn chart(input: &str, rules: Rules) -> SeatingChart {
+        let rows = input.lines().map(|line| line.parse().unwrap()).collect();
+
+        SeatingChart::new(rows, rules).unwrap()
+    }
+
+    fn settle(chart: &mut SeatingChart) -> usize {
+        while chart.step() > 0 { }
+
+        chart.occupied_count()
+    }
+
+    #[test]
+    fn example_part_one() {
+        assert_eq!(37, settle(&mut chart(EXAMPLE, Rules::part_one())));
+    }
+
+    #[test]
+    fn example_part_two() {
+        assert_eq!(26, settle(&mut chart(EXAMPLE, Rules::part_two())));
+    }
+
+    #[test]
+    fn radius_one_matches_adjacent() {
+        let mut adjacent = chart(EXAMPLE, Rules::part_one());
+        let mut radius = chart(EXAMPLE, Rules::new(Neighborhood::Radius(1), 0, 4));
+
+        for _ in 0..5 {
+            assert_eq!(adjacent.step(), radius.step());
+            assert_eq!(adjacent.to_string(), radius.to_string());
+        }
+    }
+
+    #[test]
+    fn huge_radius_covers_the_whole_chart() {
+        let mut huge = chart(EXAMPLE, Rules::new(Neighborhood::Radius(usize::MAX), 0, 4));
+        let mut whole = chart(EXAMPLE, Rules::new(Neighborhood::Radius(10), 0, 4));
+
+        for _ in 0..5 {
+            assert_eq!(whole.step(), huge.step());
+            assert_eq!(whole.to_string(), huge.to_string());
+        }
+    }
+
+    #[test]
+    fn precomputed_neighbors_match_scanning() {
+        let input = "L.LL.#.LL
+#LLL.LL.#
+L...L..L.
+..#.L#L.L";
+        let rule_sets = [
+            Rules::part_one(),
+            Rules::part_two(),
+            Rules::new(Neighborhood::Radius(2), 1, 6)
+        ];
+
+        for rules in rule_sets.iter() {
+            let mut precomputed = chart(input, *rules);
+            let mut scanning = chart(input, *rules);
+
+            for _ in 0..10 {
+                assert_eq!(scanning.step_scanning(), precomputed.step());
+                assert_eq!(scanning.to_string(), precomputed.to_string());
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_step_matches_sequential() {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let input: Vec<String> = (0..120)
+            .map(|_| {
+                (0..90)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+
+                        match state % 4 {
+                            0 => '.',
+                            1 => '#',
+                            _ => 'L'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let input = input.join("\n");
+
+        let rule_sets = [
+            Rules::part_one(),
+            Rules::part_two(),
+            Rules::new(Neighborhood::Radius(3), 2, 9),
+            Rules::new(Neighborhood::Adjacent, 0, 0)
+        ];
+
+        for rules in rule_sets.iter() {
+            for example in [EXAMPLE, input.as_str()].iter() {
+                let mut sequential = chart(example, *rules);
+                let mut parallel = chart(example, *rules);
+
+                for _ in 0..20 {
+                    assert_eq!(sequential.step_sequential(), parallel.step_parallel());
+                    assert_eq!(sequential.to_string(), parallel.to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn larger_radius_sees_further() {
+        let mut chart = chart("#.#..#", Rules::new(Neighborhood::Radius(2), 0, 1));
+        chart.step();
+
+        // The first two seats see each other at distance 2; the last is 3 away.
+        assert_eq!("L.L..#", chart.to_string());
+    }
+
+    #[test]
+    fn birth_threshold_allows_crowding() {
+        let mut chart = chart("L#L", Rules::new(Neighborhood::Adjacent, 1, 8));
+        chart.step();
+
+        assert_eq!("###", chart.to_string());
+    }
+}
@@ -1,33 +1,83 @@
 use std::error::Error;
 use std::fmt::Display;
 
+use crate::models::{ModelKind, ParseModelKindError};
+
 pub struct Config {
-    pub filename: String
+    pub filename: String,
+    pub model: ModelKind,
+    pub verbose: bool,
+    pub strict: bool,
+    pub svg: Option<String>,
+    pub geojson: Option<String>,
+    pub units_per_degree: f64,
+    pub compare: bool
 }
 
 impl Config {
+    // risk_of_rain <file> [--model heading|waypoint] [--strict] [--verbose]
+    //     [--svg FILE] [--geojson FILE [--units-per-degree N]] [--compare]
+    //
+    // --strict refuses turns that aren't a multiple of 90 degrees instead of
+    // steering at an angle. --compare exports the route of every movement
+    // model, not just the chosen one.
     pub fn new(args: &[String]) -> Result<Config, ParseConfigError> {
         if args.len() < 2 {
             return Err(ParseConfigError::NotEnoughArguments);
         }
 
         let filename = args[1].clone();
-        
-        Ok(Config { filename })
+        let mut model = ModelKind::Waypoint;
+        let mut verbose = false;
+        let mut strict = false;
+        let mut svg = None;
+        let mut geojson = None;
+        let mut units_per_degree = 1.0;
+        let mut compare = false;
+
+        let mut options = args[2..].iter();
+        while let Some(option) = options.next() {
+            match option.as_str() {
+                "--verbose" => { verbose = true; },
+                "--strict" => { strict = true; },
+                "--compare" => { compare = true; },
+                "--svg" => { svg = Some(options.next().ok_or(ParseConfigError::NotEnoughArguments)?.clone()); },
+                "--geojson" => { geojson = Some(options.next().ok_or(ParseConfigError::NotEnoughArguments)?.clone()); },
+                "--units-per-degree" => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    units_per_degree = value.parse::<f64>().ok()
+                        .filter(|units| *units > 0.0)
+                        .ok_or(ParseConfigError::InvalidScale)?;
+                },
+                "--model" => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    model = value.parse().map_err(ParseConfigError::InvalidModel)?;
+                },
+                _ => { return Err(ParseConfigError::UnknownOption(option.clone())); }
+            }
+        }
+
+        Ok(Config { filename, model, verbose, strict, svg, geojson, units_per_degree, compare })
     }
 }
 
 #[derive(Debug)]
 pub enum ParseConfigError {
-    NotEnoughArguments
+    NotEnoughArguments,
+    UnknownOption(String),
+    InvalidModel(ParseModelKindError),
+    InvalidScale
 }
 
 impl Display for ParseConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            ParseConfigError::NotEnoughArguments => "not enough arguments"
-        })
+        match self {
+            ParseConfigError::NotEnoughArguments => writeln!(f, "not enough arguments"),
+            ParseConfigError::UnknownOption(option) => writeln!(f, "unknown option '{}'", option),
+            ParseConfigError::InvalidModel(error) => write!(f, "{}", error),
+            ParseConfigError::InvalidScale => writeln!(f, "units per degree must be a positive number")
+        }
     }
 }
 
-impl Error for ParseConfigError { }
\ No newline at end of file
+impl Error for ParseConfigError { }
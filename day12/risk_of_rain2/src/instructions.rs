@@ -16,15 +16,21 @@ pub struct Instruction {
     pub value: isize
 }
 
+impl Instruction {
+    pub fn is_turn(&self) -> bool {
+        self.command == InstructionCommand::Left || self.command == InstructionCommand::Right
+    }
+}
+
 impl FromStr for Instruction {
     type Err = ParseInstructionError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() == 0 {
+        if s.is_empty() {
             Err(ParseInstructionError::Empty)
         } else {
-            let (first, rest) = s.split_at(1);
-            let first_char = first.chars().nth(0).unwrap();
+            let first_char = s.chars().next().unwrap();
+            let rest = &s[first_char.len_utf8()..];
 
             let command = match first_char {
                 'N' => Ok(InstructionCommand::North),
@@ -45,6 +51,95 @@ impl FromStr for Instruction {
     }
 }
 
+// A whole route's worth of instructions, one per line. The same set can be run
+// under any movement model.
+#[derive(Debug, PartialEq)]
+pub struct InstructionSet {
+    instructions: Vec<Instruction>
+}
+
+impl InstructionSet {
+    pub fn new(instructions: Vec<Instruction>) -> InstructionSet {
+        InstructionSet { instructions }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Instruction> {
+        self.instructions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    // Strict mode only allows the quarter turns the puzzle was written for.
+    pub fn validate_right_angles(&self) -> Result<(), ValidateInstructionSetError> {
+        let unsupported_turns: Vec<(usize, isize)> = self.instructions.iter()
+            .enumerate()
+            .filter(|(_, instruction)| instruction.is_turn() && instruction.value % 90 != 0)
+            .map(|(i, instruction)| (i + 1, instruction.value))
+            .collect();
+
+        if unsupported_turns.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidateInstructionSetError { unsupported_turns })
+        }
+    }
+}
+
+impl FromStr for InstructionSet {
+    type Err = ParseInstructionSetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut instructions = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let instruction = line.trim().parse::<Instruction>()
+                .map_err(|error| ParseInstructionSetError { line_number: i + 1, error })?;
+
+            instructions.push(instruction);
+        }
+
+        Ok(InstructionSet { instructions })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ValidateInstructionSetError {
+    // (line number, degrees) for every turn that isn't a multiple of 90.
+    pub unsupported_turns: Vec<(usize, isize)>
+}
+
+impl Display for ValidateInstructionSetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (line_number, degrees) in self.unsupported_turns.iter() {
+            writeln!(f, "line {}: can't turn {} degrees in strict mode", line_number, degrees)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for ValidateInstructionSetError { }
+
+#[derive(Debug, PartialEq)]
+pub struct ParseInstructionSetError {
+    pub line_number: usize,
+    pub error: ParseInstructionError
+}
+
+impl Display for ParseInstructionSetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line_number, self.error)
+    }
+}
+
+impl Error for ParseInstructionSetError { }
+
 #[derive(Debug, PartialEq)]
 pub enum InstructionCommand {
     North,
@@ -92,4 +187,23 @@ mod tests {
 
         assert_eq!(Err(ParseInstructionError::Empty), result);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn parse_set_reports_line() {
+        let result = "N5\nF10\nX3".parse::<InstructionSet>();
+
+        assert_eq!(Err(ParseInstructionSetError { line_number: 3, error: ParseInstructionError::InvalidCommand('X') }), result);
+    }
+
+    #[test]
+    fn validate_right_angles() {
+        let valid = "R90\nL270\nF45".parse::<InstructionSet>().unwrap();
+        let invalid = "R90\nL45\nF10\nR100".parse::<InstructionSet>().unwrap();
+
+        assert_eq!(Ok(()), valid.validate_right_angles());
+        assert_eq!(
+            Err(ValidateInstructionSetError { unsupported_turns: vec![(2, 45), (4, 100)] }),
+            invalid.validate_right_angles()
+        );
+    }
+}
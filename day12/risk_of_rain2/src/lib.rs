@@ -1,55 +1,68 @@
 use std::error::Error;
-use std::fmt::Display;
-use std::fs::{File};
-use std::io::{self, BufRead};
+use std::fs;
 use std::path::Path;
 
 use config::Config;
-use instructions::Instruction;
-use ship::Ship;
+use instructions::InstructionSet;
+use models::ModelKind;
+use navigator::Navigator;
+use route::NamedRoute;
 
 pub mod config;
-mod ship;
-mod instructions;
+pub mod instructions;
+pub mod models;
+pub mod navigator;
+pub mod route;
 
 pub fn run(config: Config) -> Result<isize, Box<dyn Error>> {
-    let filename = config.filename;
-
-    let mut instructions = Vec::new();
-
-    let lines = read_lines(filename)?;
-    for line in lines {
-        let instruction = line?.parse::<Instruction>()?;
-        instructions.push(instruction);
+    let instructions = read_instructions(&config.filename)?;
+    if config.strict {
+        instructions.validate_right_angles()?;
     }
 
-    let mut ship = Ship::new();
+    let mut navigator = Navigator::new(config.model.build());
 
     for instruction in instructions.iter() {
-        ship.apply(instruction);
-        println!("{:?} -> {:?}", instruction, ship);
+        navigator.apply(instruction);
+        if config.verbose {
+            println!("{:?} -> {} {:?}", instruction, navigator.position(), navigator.model());
+        }
     }
 
-    Ok(ship.manhattan_distance())
-}
+    if config.svg.is_some() || config.geojson.is_some() {
+        export_routes(&config, &instructions, &navigator)?;
+    }
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    Ok(navigator.rounded_distance())
 }
 
-#[derive(Debug)]
-pub enum ApplicationError {
-    AnError
-}
+fn export_routes(config: &Config, instructions: &InstructionSet, navigator: &Navigator) -> Result<(), Box<dyn Error>> {
+    let mut others = Vec::new();
+    if config.compare {
+        for kind in ModelKind::all().iter().filter(|kind| **kind != config.model) {
+            let mut other = Navigator::new(kind.build());
+            other.run(instructions);
+            others.push((kind.to_string(), other));
+        }
+    }
 
-impl Display for ApplicationError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            AnError => "an error occurred"  
-        })
+    let model_name = config.model.to_string();
+    let mut routes = vec![NamedRoute::new(&model_name, navigator.route())];
+    for (name, other) in others.iter() {
+        routes.push(NamedRoute::new(name, other.route()));
     }
+
+    if let Some(filename) = &config.svg {
+        fs::write(filename, route::to_svg(&routes))?;
+    }
+    if let Some(filename) = &config.geojson {
+        fs::write(filename, route::to_geojson(&routes, config.units_per_degree))?;
+    }
+
+    Ok(())
 }
 
-impl Error for ApplicationError { }
+pub fn read_instructions<P>(filename: P) -> Result<InstructionSet, Box<dyn Error>>
+where P: AsRef<Path>, {
+    Ok(fs::read_to_string(filename)?.parse::<InstructionSet>()?)
+}
@@ -0,0 +1,255 @@
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+use crate::instructions::{Instruction, InstructionCommand};
+use crate::navigator::Position;
+
+// Decides how an instruction moves the ship. The navigator owns the ship's
+// position; a model keeps whatever else it needs to steer, like a heading or
+// a waypoint.
+pub trait MovementModel: Debug {
+    fn apply(&mut self, position: &mut Position, instruction: &Instruction);
+
+    // The waypoint relative to the ship, for models that steer by one.
+    fn waypoint(&self) -> Option<Position> {
+        None
+    }
+}
+
+// The ship turns in place and F moves it along its heading; N/S/E/W move the
+// ship directly. The heading is a compass bearing in degrees, so turns needn't
+// be multiples of 90.
+#[derive(Debug)]
+pub struct Heading {
+    bearing: isize
+}
+
+impl Heading {
+    pub fn new(direction: ShipDirection) -> Heading {
+        Heading { bearing: direction.bearing() }
+    }
+
+    pub fn bearing(&self) -> isize {
+        self.bearing
+    }
+
+    // None when the ship isn't facing a cardinal direction.
+    pub fn direction(&self) -> Option<ShipDirection> {
+        ShipDirection::from_bearing(self.bearing)
+    }
+
+    fn rotate(&mut self, value: isize) {
+        self.bearing = (self.bearing + value).rem_euclid(360);
+    }
+}
+
+impl Default for Heading {
+    fn default() -> Self {
+        Heading::new(ShipDirection::East)
+    }
+}
+
+impl MovementModel for Heading {
+    fn apply(&mut self, position: &mut Position, instruction: &Instruction) {
+        let value = instruction.value;
+
+        match instruction.command {
+            InstructionCommand::North => { *position = position.offset(Position::bearing(0), value); }
+            InstructionCommand::South => { *position = position.offset(Position::bearing(180), value); }
+            InstructionCommand::East => { *position = position.offset(Position::bearing(90), value); }
+            InstructionCommand::West => { *position = position.offset(Position::bearing(270), value); }
+            InstructionCommand::Left => { self.rotate(-turn(value)); }
+            InstructionCommand::Right => { self.rotate(turn(value)); }
+            InstructionCommand::Forward => { *position = position.offset(Position::bearing(self.bearing), value); }
+        }
+    }
+}
+
+// N/S/E/W and L/R move a waypoint relative to the ship, and F moves the ship
+// towards the waypoint that many times.
+#[derive(Debug)]
+pub struct Waypoint {
+    waypoint: Position
+}
+
+impl Waypoint {
+    pub fn new(waypoint: Position) -> Waypoint {
+        Waypoint { waypoint }
+    }
+
+    pub fn waypoint(&self) -> Position {
+        self.waypoint
+    }
+}
+
+impl Default for Waypoint {
+    fn default() -> Self {
+        Waypoint::new(Position::new(10.0, 1.0))
+    }
+}
+
+impl MovementModel for Waypoint {
+    fn apply(&mut self, position: &mut Position, instruction: &Instruction) {
+        let value = instruction.value;
+
+        match instruction.command {
+            InstructionCommand::North => { self.waypoint = self.waypoint.offset(Position::bearing(0), value); }
+            InstructionCommand::South => { self.waypoint = self.waypoint.offset(Position::bearing(180), value); }
+            InstructionCommand::East => { self.waypoint = self.waypoint.offset(Position::bearing(90), value); }
+            InstructionCommand::West => { self.waypoint = self.waypoint.offset(Position::bearing(270), value); }
+            InstructionCommand::Left => { self.waypoint = self.waypoint.rotate(-turn(value)); }
+            InstructionCommand::Right => { self.waypoint = self.waypoint.rotate(turn(value)); }
+            InstructionCommand::Forward => { *position = position.offset(self.waypoint, value); }
+        }
+    }
+
+    fn waypoint(&self) -> Option<Position> {
+        Some(self.waypoint)
+    }
+}
+
+// A turn reduced to 0..360 degrees, so that any turn the parser accepts can be
+// negated or added to a bearing without overflowing.
+fn turn(value: isize) -> isize {
+    value.rem_euclid(360)
+}
+
+// The movement models that can be picked by name.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModelKind {
+    Heading,
+    Waypoint
+}
+
+impl ModelKind {
+    pub fn all() -> [ModelKind; 2] {
+        [ModelKind::Heading, ModelKind::Waypoint]
+    }
+
+    pub fn build(&self) -> Box<dyn MovementModel> {
+        match self {
+            ModelKind::Heading => Box::new(Heading::default()),
+            ModelKind::Waypoint => Box::new(Waypoint::default())
+        }
+    }
+}
+
+impl Display for ModelKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            ModelKind::Heading => "heading",
+            ModelKind::Waypoint => "waypoint"
+        })
+    }
+}
+
+impl FromStr for ModelKind {
+    type Err = ParseModelKindError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "heading" => Ok(ModelKind::Heading),
+            "waypoint" => Ok(ModelKind::Waypoint),
+            _ => Err(ParseModelKindError { name: String::from(s) })
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseModelKindError {
+    name: String
+}
+
+impl Display for ParseModelKindError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "unknown movement model '{}', expected heading or waypoint", self.name)
+    }
+}
+
+impl Error for ParseModelKindError { }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShipDirection {
+    North,
+    South,
+    East,
+    West
+}
+
+impl ShipDirection {
+    pub fn bearing(&self) -> isize {
+        match self {
+            ShipDirection::North => 0,
+            ShipDirection::East => 90,
+            ShipDirection::South => 180,
+            ShipDirection::West => 270
+        }
+    }
+
+    pub fn from_bearing(degrees: isize) -> Option<ShipDirection> {
+        match degrees.rem_euclid(360) {
+            0 => Some(ShipDirection::North),
+            90 => Some(ShipDirection::East),
+            180 => Some(ShipDirection::South),
+            270 => Some(ShipDirection::West),
+            _ => None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waypoint_rotates_around_ship() {
+        let mut model = Waypoint::new(Position::new(10.0, 4.0));
+        let mut position = Position::default();
+
+        model.apply(&mut position, &"R90".parse().unwrap());
+        assert_eq!(Position::new(4.0, -10.0), model.waypoint());
+
+        model.apply(&mut position, &"L270".parse().unwrap());
+        assert_eq!(Position::new(-10.0, -4.0), model.waypoint());
+    }
+
+    #[test]
+    fn heading_turns_any_angle() {
+        let mut model = Heading::default();
+        let mut position = Position::default();
+
+        model.apply(&mut position, &"L45".parse().unwrap());
+        assert_eq!(45, model.bearing());
+        assert_eq!(None, model.direction());
+
+        model.apply(&mut position, &"F10".parse().unwrap());
+        assert_eq!(Position::new(7.071067812, 7.071067812), position);
+
+        model.apply(&mut position, &"L45".parse().unwrap());
+        assert_eq!(Some(ShipDirection::North), model.direction());
+    }
+
+    #[test]
+    fn extreme_turns_dont_overflow() {
+        // isize::MIN is 352 degrees past a whole turn and isize::MAX is 7.
+        let turns = [
+            (format!("L{}", isize::MIN), 98, -352),
+            (format!("R{}", isize::MIN), 82, 352),
+            (format!("L{}", isize::MAX), 83, -7),
+            (format!("R{}", isize::MAX), 97, 7)
+        ];
+
+        for (turn, bearing, degrees) in turns.iter() {
+            let instruction = turn.parse().unwrap();
+
+            let mut heading = Heading::default();
+            heading.apply(&mut Position::default(), &instruction);
+            assert_eq!(*bearing, heading.bearing(), "{}", turn);
+
+            let mut waypoint = Waypoint::default();
+            waypoint.apply(&mut Position::default(), &instruction);
+            assert_eq!(Position::new(10.0, 1.0).rotate(*degrees), waypoint.waypoint(), "{}", turn);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::Display;
+
+use crate::instructions::{Instruction, InstructionSet};
+use crate::models::MovementModel;
+
+// Coordinates produced by turns that aren't a multiple of 90 degrees are
+// rounded to this many parts per unit, so that e.g. two 45 degree turns land
+// exactly where one 90 degree turn would.
+const PRECISION: f64 = 1e9;
+
+// East and north are positive. Coordinates stay whole numbers as long as every
+// turn is a multiple of 90 degrees; other angles are worked out with sin and
+// cos and rounded to PRECISION.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64
+}
+
+impl Position {
+    pub fn new(x: f64, y: f64) -> Position {
+        // Adding 0.0 turns -0.0 into 0.0, so it never prints as "-0".
+        Position { x: x + 0.0, y: y + 0.0 }
+    }
+
+    // The unit vector for a compass bearing, clockwise from north. It isn't
+    // rounded, so that moving several units along it is only rounded once.
+    pub fn bearing(degrees: isize) -> Position {
+        match degrees.rem_euclid(360) {
+            0 => Position::new(0.0, 1.0),
+            90 => Position::new(1.0, 0.0),
+            180 => Position::new(0.0, -1.0),
+            270 => Position::new(-1.0, 0.0),
+            degrees => {
+                let (sin, cos) = (degrees as f64).to_radians().sin_cos();
+
+                Position::new(sin, cos)
+            }
+        }
+    }
+
+    // Rotates clockwise around the origin.
+    pub fn rotate(&self, degrees: isize) -> Position {
+        match degrees.rem_euclid(360) {
+            0 => *self,
+            90 => Position::new(self.y, -self.x),
+            180 => Position::new(-self.x, -self.y),
+            270 => Position::new(-self.y, self.x),
+            degrees => {
+                let (sin, cos) = (-(degrees as f64).to_radians()).sin_cos();
+
+                Position::new(
+                    round(self.x * cos - self.y * sin),
+                    round(self.x * sin + self.y * cos)
+                )
+            }
+        }
+    }
+
+    pub fn offset(&self, other: Position, times: isize) -> Position {
+        let times = times as f64;
+
+        Position::new(round(self.x + other.x * times), round(self.y + other.y * times))
+    }
+
+    pub fn manhattan_distance(&self) -> f64 {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+fn round(value: f64) -> f64 {
+    (value * PRECISION).round() / PRECISION
+}
+
+// Where the ship (and its waypoint, for models that steer by one) was after an
+// instruction. The waypoint is relative to the ship, as instructions see it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutePoint {
+    pub position: Position,
+    pub waypoint: Option<Position>
+}
+
+impl RoutePoint {
+    // The waypoint's place on the map rather than relative to the ship.
+    pub fn absolute_waypoint(&self) -> Option<Position> {
+        self.waypoint.map(|waypoint| self.position.offset(waypoint, 1))
+    }
+}
+
+#[derive(Debug)]
+pub struct Navigator {
+    position: Position,
+    model: Box<dyn MovementModel>,
+    route: Vec<RoutePoint>
+}
+
+impl Navigator {
+    pub fn new(model: Box<dyn MovementModel>) -> Navigator {
+        let position = Position::default();
+        let route = vec![RoutePoint { position, waypoint: model.waypoint() }];
+
+        Navigator { position, model, route }
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn model(&self) -> &dyn MovementModel {
+        self.model.as_ref()
+    }
+
+    // The starting point followed by one point per instruction applied.
+    pub fn route(&self) -> &[RoutePoint] {
+        &self.route
+    }
+
+    pub fn apply(&mut self, instruction: &Instruction) {
+        self.model.apply(&mut self.position, instruction);
+        self.route.push(RoutePoint { position: self.position, waypoint: self.model.waypoint() });
+    }
+
+    pub fn run(&mut self, instructions: &InstructionSet) {
+        for instruction in instructions.iter() {
+            self.apply(instruction);
+        }
+    }
+
+    pub fn manhattan_distance(&self) -> f64 {
+        self.position.manhattan_distance()
+    }
+
+    // The distance rounded to the nearest whole number, halves away from zero.
+    pub fn rounded_distance(&self) -> isize {
+        self.manhattan_distance().round() as isize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModelKind;
+
+    const EXAMPLE: &str = "F10\nN3\nF7\nR90\nF11";
+
+    fn navigate(kind: ModelKind) -> Navigator {
+        let instructions = EXAMPLE.parse::<InstructionSet>().unwrap();
+        let mut navigator = Navigator::new(kind.build());
+        navigator.run(&instructions);
+
+        navigator
+    }
+
+    #[test]
+    fn example_with_heading() {
+        let navigator = navigate(ModelKind::Heading);
+
+        assert_eq!(Position::new(17.0, -8.0), navigator.position());
+        assert_eq!(25, navigator.rounded_distance());
+    }
+
+    #[test]
+    fn example_with_waypoint() {
+        let navigator = navigate(ModelKind::Waypoint);
+
+        assert_eq!(Position::new(214.0, -72.0), navigator.position());
+        assert_eq!(286, navigator.rounded_distance());
+    }
+
+    #[test]
+    fn right_angle_rotations_are_exact() {
+        let position = Position::new(3.0, 7.0);
+
+        assert_eq!(Position::new(7.0, -3.0), position.rotate(90));
+        assert_eq!(Position::new(-3.0, -7.0), position.rotate(-180));
+        assert_eq!(Position::new(-7.0, 3.0), position.rotate(-90));
+        assert_eq!(position, position.rotate(720));
+    }
+
+    #[test]
+    fn arbitrary_rotations_are_rounded() {
+        let position = Position::new(10.0, 4.0);
+
+        assert_eq!(position.rotate(90), position.rotate(45).rotate(45));
+        assert_eq!(position.rotate(90), position.rotate(30).rotate(30).rotate(30));
+        assert_eq!(Position::new(7.071067812, -7.071067812), Position::default().offset(Position::bearing(135), 10));
+    }
+
+    #[test]
+    fn arbitrary_angles_with_waypoint() {
+        let instructions = "R45\nF1\nL45\nF2".parse::<InstructionSet>().unwrap();
+        let mut navigator = Navigator::new(ModelKind::Waypoint.build());
+        navigator.run(&instructions);
+
+        // (10, 1) turned 45 degrees clockwise is (7.778174593, -6.363961031).
+        assert_eq!(Position::new(27.778174593, -4.363961031), navigator.position());
+        assert_eq!(32, navigator.rounded_distance());
+    }
+
+    #[test]
+    fn route_is_recorded() {
+        let heading = navigate(ModelKind::Heading);
+        let waypoint = navigate(ModelKind::Waypoint);
+
+        assert_eq!(6, heading.route().len());
+        assert_eq!(None, heading.route()[0].waypoint);
+        assert_eq!(Position::new(17.0, 3.0), heading.route()[3].position);
+
+        let last = waypoint.route()[5];
+        assert_eq!(Some(Position::new(4.0, -10.0)), last.waypoint);
+        assert_eq!(Some(Position::new(218.0, -82.0)), last.absolute_waypoint());
+    }
+}
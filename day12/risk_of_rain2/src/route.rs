@@ -0,0 +1,156 @@
+use crate::navigator::{Position, RoutePoint};
+
+const COLORS: [&str; 4] = ["#1f77b4", "#d62728", "#2ca02c", "#9467bd"];
+
+// A recorded route and the name it's exported under, usually the movement
+// model that produced it.
+pub struct NamedRoute<'a> {
+    pub name: String,
+    pub points: &'a [RoutePoint]
+}
+
+impl<'a> NamedRoute<'a> {
+    pub fn new(name: &str, points: &'a [RoutePoint]) -> NamedRoute<'a> {
+        NamedRoute { name: String::from(name), points }
+    }
+
+    fn positions(&self) -> Vec<Position> {
+        self.points.iter().map(|point| point.position).collect()
+    }
+
+    fn waypoints(&self) -> Vec<Position> {
+        self.points.iter().filter_map(|point| point.absolute_waypoint()).collect()
+    }
+}
+
+// Draws every route as a polyline, with its waypoint track dashed in the same
+// colour. North is up, and the view is fitted around everything drawn.
+pub fn to_svg(routes: &[NamedRoute]) -> String {
+    let all: Vec<Position> = routes.iter()
+        .flat_map(|route| route.positions().into_iter().chain(route.waypoints()))
+        .collect();
+
+    let (min_x, max_x) = bounds(all.iter().map(|position| position.x));
+    let (min_y, max_y) = bounds(all.iter().map(|position| position.y));
+    let margin = ((max_x - min_x).max(max_y - min_y) * 0.05).max(1.0);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n",
+        min_x - margin, -max_y - margin, max_x - min_x + 2.0 * margin, max_y - min_y + 2.0 * margin
+    );
+
+    for (i, route) in routes.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+
+        let waypoints = route.waypoints();
+        if !waypoints.is_empty() {
+            svg.push_str(&format!(
+                "  <polyline fill=\"none\" stroke=\"{}\" stroke-dasharray=\"4 4\" stroke-opacity=\"0.5\" vector-effect=\"non-scaling-stroke\" points=\"{}\"><title>{} waypoint</title></polyline>\n",
+                color, svg_points(&waypoints), escape(&route.name)
+            ));
+        }
+
+        svg.push_str(&format!(
+            "  <polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" vector-effect=\"non-scaling-stroke\" points=\"{}\"><title>{}</title></polyline>\n",
+            color, svg_points(&route.positions()), escape(&route.name)
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+// One LineString feature per route, plus one for its waypoint track if it has
+// one. Positions are divided by units_per_degree to get longitude (east) and
+// latitude (north); GeoJSON viewers expect degrees, so large routes need
+// scaling down to stay on the map.
+pub fn to_geojson(routes: &[NamedRoute], units_per_degree: f64) -> String {
+    let mut features = Vec::new();
+
+    for route in routes {
+        features.push(geojson_feature(&route.name, "ship", &route.positions(), units_per_degree));
+
+        let waypoints = route.waypoints();
+        if !waypoints.is_empty() {
+            features.push(geojson_feature(&route.name, "waypoint", &waypoints, units_per_degree));
+        }
+    }
+
+    format!("{{\"type\":\"FeatureCollection\",\"features\":[\n{}\n]}}\n", features.join(",\n"))
+}
+
+fn geojson_feature(name: &str, track: &str, positions: &[Position], units_per_degree: f64) -> String {
+    let coordinates: Vec<String> = positions.iter()
+        .map(|position| format!("[{},{}]", position.x / units_per_degree, position.y / units_per_degree))
+        .collect();
+
+    format!(
+        "{{\"type\":\"Feature\",\"properties\":{{\"name\":\"{}\",\"track\":\"{}\"}},\"geometry\":{{\"type\":\"LineString\",\"coordinates\":[{}]}}}}",
+        escape(name), track, coordinates.join(",")
+    )
+}
+
+fn svg_points(positions: &[Position]) -> String {
+    let points: Vec<String> = positions.iter()
+        .map(|position| format!("{},{}", position.x, -position.y + 0.0))
+        .collect();
+
+    points.join(" ")
+}
+
+fn bounds<I: Iterator<Item = f64>>(values: I) -> (f64, f64) {
+    values.fold((0.0, 0.0), |(min, max), value| (min.min(value), max.max(value)))
+}
+
+// Good enough for both XML text and JSON strings, given names are plain words.
+fn escape(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_control())
+        .map(|c| match c {
+            '<' | '>' | '&' | '"' | '\\' => '_',
+            c => c
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::InstructionSet;
+    use crate::models::ModelKind;
+    use crate::navigator::Navigator;
+
+    fn navigate(kind: ModelKind) -> Navigator {
+        let instructions = "F10\nN3\nF7\nR90\nF11".parse::<InstructionSet>().unwrap();
+        let mut navigator = Navigator::new(kind.build());
+        navigator.run(&instructions);
+
+        navigator
+    }
+
+    #[test]
+    fn svg_has_a_polyline_per_track() {
+        let heading = navigate(ModelKind::Heading);
+        let waypoint = navigate(ModelKind::Waypoint);
+        let svg = to_svg(&[
+            NamedRoute::new("heading", heading.route()),
+            NamedRoute::new("waypoint", waypoint.route())
+        ]);
+
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(3, svg.matches("<polyline").count());
+        assert!(svg.contains("points=\"0,0 10,0 10,-3 17,-3 17,-3 17,8\""));
+        assert!(svg.contains("<title>waypoint waypoint</title>"));
+    }
+
+    #[test]
+    fn geojson_has_a_line_string_per_track() {
+        let heading = navigate(ModelKind::Heading);
+        let geojson = to_geojson(&[NamedRoute::new("heading", heading.route())], 10.0);
+
+        assert!(geojson.starts_with("{\"type\":\"FeatureCollection\""));
+        assert_eq!(1, geojson.matches("\"LineString\"").count());
+        assert!(geojson.contains("\"coordinates\":[[0,0],[1,0],[1,0.3],[1.7,0.3],[1.7,0.3],[1.7,-0.8]]"));
+    }
+}
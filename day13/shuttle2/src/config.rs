@@ -1,33 +1,120 @@
 use std::error::Error;
 use std::fmt::Display;
 
+use crate::query::{Alignment, ParseAlignmentError};
+
+const DEFAULT_COUNT: usize = 10;
+
+pub enum Command {
+    // The earliest time every bus leaves at its offset in the schedule.
+    EarliestTime,
+    // The next `count` departures from `after` on. Without --after, the
+    // timestamp on the input's first line is used.
+    Next { after: Option<i128>, count: usize },
+    // Up to `count` times in from..=to at which the given buses line up.
+    Align { alignments: Vec<Alignment>, from: i128, to: i128, count: usize },
+    // How often the whole schedule repeats.
+    Period
+}
+
 pub struct Config {
-    pub filename: String
+    pub filename: String,
+    pub command: Command
 }
 
 impl Config {
+    // shuttle <file>
+    // shuttle query next <file> [--after T] [--count N]
+    // shuttle query align <file> --buses BUS:OFFSET,... [--from T] [--to T] [--count N]
+    // shuttle query period <file>
     pub fn new(args: &[String]) -> Result<Config, ParseConfigError> {
         if args.len() < 2 {
             return Err(ParseConfigError::NotEnoughArguments);
         }
 
-        let filename = args[1].clone();
-        
-        Ok(Config { filename })
+        if args[1] != "query" {
+            return Ok(Config { filename: args[1].clone(), command: Command::EarliestTime });
+        }
+
+        if args.len() < 4 {
+            return Err(ParseConfigError::NotEnoughArguments);
+        }
+
+        let query = args[2].as_str();
+        if !["next", "align", "period"].contains(&query) {
+            return Err(ParseConfigError::UnknownQuery(String::from(query)));
+        }
+
+        let filename = args[3].clone();
+        let mut after = None;
+        let mut count = DEFAULT_COUNT;
+        let mut alignments = None;
+        let mut from = 0;
+        let mut to = i128::MAX;
+
+        let mut options = args[4..].iter();
+        while let Some(option) = options.next() {
+            match (query, option.as_str()) {
+                ("next", "--after") => { after = Some(parse_time(options.next())?); },
+                ("align", "--from") => { from = parse_time(options.next())?; },
+                ("align", "--to") => { to = parse_time(options.next())?; },
+                ("next", "--count") | ("align", "--count") => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    count = value.parse().map_err(|_| ParseConfigError::InvalidCount)?;
+                },
+                ("align", "--buses") => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    alignments = Some(value.split(',')
+                        .map(|alignment| alignment.parse())
+                        .collect::<Result<Vec<Alignment>, _>>()
+                        .map_err(ParseConfigError::InvalidAlignment)?);
+                },
+                _ => { return Err(ParseConfigError::UnknownOption(option.clone())); }
+            }
+        }
+
+        let command = match query {
+            "next" => Command::Next { after, count },
+            "align" => {
+                let alignments = alignments.ok_or(ParseConfigError::MissingBuses)?;
+                Command::Align { alignments, from, to, count }
+            },
+            _ => Command::Period
+        };
+
+        Ok(Config { filename, command })
     }
 }
 
+fn parse_time(value: Option<&String>) -> Result<i128, ParseConfigError> {
+    let value = value.ok_or(ParseConfigError::NotEnoughArguments)?;
+
+    value.parse().map_err(|_| ParseConfigError::InvalidTime(value.clone()))
+}
+
 #[derive(Debug)]
 pub enum ParseConfigError {
-    NotEnoughArguments
+    NotEnoughArguments,
+    UnknownOption(String),
+    UnknownQuery(String),
+    InvalidTime(String),
+    InvalidCount,
+    InvalidAlignment(ParseAlignmentError),
+    MissingBuses
 }
 
 impl Display for ParseConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            ParseConfigError::NotEnoughArguments => "not enough arguments"
-        })
+        match self {
+            ParseConfigError::NotEnoughArguments => writeln!(f, "not enough arguments"),
+            ParseConfigError::UnknownOption(option) => writeln!(f, "unknown option '{}'", option),
+            ParseConfigError::UnknownQuery(query) => writeln!(f, "unknown query '{}', expected next, align or period", query),
+            ParseConfigError::InvalidTime(time) => writeln!(f, "'{}' is not a valid time", time),
+            ParseConfigError::InvalidCount => writeln!(f, "count must be a whole number"),
+            ParseConfigError::InvalidAlignment(error) => write!(f, "{}", error),
+            ParseConfigError::MissingBuses => writeln!(f, "align needs --buses BUS:OFFSET,...")
+        }
     }
 }
 
-impl Error for ParseConfigError { }
\ No newline at end of file
+impl Error for ParseConfigError { }
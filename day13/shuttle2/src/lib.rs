@@ -4,13 +4,16 @@ use std::fs::{File};
 use std::io::{self, BufRead};
 use std::path::Path;
 
-use config::Config;
+use config::{Command, Config};
+use query::AlignedTimes;
 use schedule::Schedule;
 
 pub mod config;
-mod schedule;
+pub mod number_theory;
+pub mod query;
+pub mod schedule;
 
-pub fn run(config: Config) -> Result<i64, Box<dyn Error>> {
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
     let filename = config.filename;
 
     let lines = read_lines(filename)?;
@@ -18,48 +21,36 @@ pub fn run(config: Config) -> Result<i64, Box<dyn Error>> {
         return Err(ApplicationError::WrongLineCount.into());
     }
 
-    // Day 2, ignore line 1
-    // let timestamp = lines[0].parse::<usize>()
-    //     .map_err(|_| ApplicationError::InvalidTimestamp)?;
-
     let schedule = lines[1].parse::<Schedule>()?;
 
-    let earliest_time = schedule.find_earliest_time();
-
-    Ok(earliest_time)
-}
-
-fn find_best_bus(timestamp: usize, schedule: Vec<usize>) -> Option<BusScore> {
-    let mut best_score: Option<BusScore> = None;
-
-    for bus in schedule {
-        let d = timestamp / bus;
-        let remainder = timestamp - d * bus;
-
-        if remainder == 0 {
-            return Some(BusScore { id: bus, minutes_to_wait: 0 });
-        }
-
-        let minutes_to_wait = bus - remainder;
-
-        if let Some(existing_score) = &best_score {
-            if minutes_to_wait < existing_score.minutes_to_wait {
-                best_score = Some(BusScore { id: bus, minutes_to_wait });
-            }
-        } else {
-            best_score = Some(BusScore { id: bus, minutes_to_wait });
-        }
-        
-        println!("{} / {} = {}; rem {}", timestamp, bus, d, remainder);
-    }
-
-    best_score
-}
-
-#[derive(Debug)]
-struct BusScore {
-    id: usize,
-    minutes_to_wait: usize
+    let output = match config.command {
+        Command::EarliestTime => schedule.find_earliest_time()?.to_string(),
+        Command::Next { after, count } => {
+            // Part 1 inputs start with the timestamp to search from.
+            let after = match after {
+                Some(after) => after,
+                None => lines[0].parse::<i128>().map_err(|_| ApplicationError::InvalidTimestamp)?
+            };
+
+            let departures = query::next_departures(&schedule, after, count)?;
+            let lines: Vec<String> = departures.iter()
+                .map(|departure| format!("{} (wait {})", departure, departure.time - after))
+                .collect();
+
+            lines.join("\n")
+        },
+        Command::Align { alignments, from, to, count } => {
+            let times: Vec<String> = AlignedTimes::new(&schedule, &alignments, from, to)?
+                .take(count)
+                .map(|time| time.to_string())
+                .collect();
+
+            times.join("\n")
+        },
+        Command::Period => query::period(&schedule)?.to_string()
+    };
+
+    Ok(output)
 }
 
 fn read_lines<P>(filename: P) -> io::Result<Vec<String>>
@@ -85,7 +76,7 @@ impl Display for ApplicationError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", match self {
             ApplicationError::WrongLineCount => "input was not exactly 2 lines",
-            ApplicationError::InvalidTimestamp => "timestamp was invalid",
+            ApplicationError::InvalidTimestamp => "first line is not a timestamp, pass --after T instead",
             ApplicationError::InvalidSchedule => "schedule was invalid",
             ApplicationError::BestScoreNotFound => "best score could not be found"
         })
@@ -0,0 +1,200 @@
+use std::error::Error;
+use std::fmt::Display;
+
+// Returns (g, x, y) with a * x + b * y = g, where g is the greatest common
+// divisor of a and b. g is never negative.
+//
+// Neither argument may be i128::MIN: its gcd with 0 is 2^127, which doesn't
+// fit, and the coefficients can overflow on the way there. Congruences only
+// ever pass positive moduli.
+pub fn extended_euclid(a: i128, b: i128) -> (i128, i128, i128) {
+    debug_assert!(a != i128::MIN && b != i128::MIN, "extended_euclid doesn't support i128::MIN");
+
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+
+    while r1 != 0 {
+        let q = r0.div_euclid(r1);
+
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+
+    if r0 < 0 {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+// Like extended_euclid, neither argument may be i128::MIN.
+pub fn gcd(a: i128, b: i128) -> i128 {
+    extended_euclid(a, b).0
+}
+
+// None when the result doesn't fit. Neither argument may be i128::MIN.
+pub fn lcm(a: i128, b: i128) -> Option<i128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    (a / gcd(a, b)).checked_mul(b).and_then(i128::checked_abs)
+}
+
+// x = residue (mod modulus), with the residue kept in 0..modulus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Congruence {
+    pub residue: i128,
+    pub modulus: i128
+}
+
+impl Congruence {
+    pub fn new(residue: i128, modulus: i128) -> Result<Congruence, CongruenceError> {
+        if modulus <= 0 {
+            return Err(CongruenceError::InvalidModulus { modulus });
+        }
+
+        Ok(Congruence { residue: residue.rem_euclid(modulus), modulus })
+    }
+
+    // Combines two congruences into the one satisfied by exactly the numbers
+    // that satisfy both. The moduli needn't be coprime; when they share a
+    // factor the residues have to agree modulo it, or nothing satisfies both.
+    pub fn merge(&self, other: &Congruence) -> Result<Congruence, CongruenceError> {
+        let (g, p, _) = extended_euclid(self.modulus, other.modulus);
+        let difference = other.residue - self.residue;
+
+        if difference % g != 0 {
+            return Err(CongruenceError::Unsolvable { first: *self, second: *other });
+        }
+
+        // self.residue + self.modulus * k satisfies other when
+        // k = (difference / g) * p (mod other.modulus / g).
+        let step = other.modulus / g;
+        let modulus = (self.modulus / g).checked_mul(other.modulus)
+            .ok_or(CongruenceError::Overflow)?;
+        let k = mul_mod(difference / g, p, step)?;
+        let residue = self.modulus.checked_mul(k)
+            .and_then(|offset| offset.checked_add(self.residue))
+            .ok_or(CongruenceError::Overflow)?;
+
+        Congruence::new(residue, modulus)
+    }
+}
+
+impl Display for Congruence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "x = {} (mod {})", self.residue, self.modulus)
+    }
+}
+
+// The smallest non-negative solution to every congruence, along with the
+// modulus it repeats with. An empty system is satisfied by everything.
+pub fn solve(congruences: &[Congruence]) -> Result<Congruence, CongruenceError> {
+    congruences.iter().try_fold(Congruence { residue: 0, modulus: 1 }, |accum, congruence| accum.merge(congruence))
+}
+
+fn mul_mod(a: i128, b: i128, modulus: i128) -> Result<i128, CongruenceError> {
+    let a = a.rem_euclid(modulus);
+    let b = b.rem_euclid(modulus);
+
+    a.checked_mul(b)
+        .map(|product| product.rem_euclid(modulus))
+        .ok_or(CongruenceError::Overflow)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CongruenceError {
+    InvalidModulus { modulus: i128 },
+    Unsolvable { first: Congruence, second: Congruence },
+    Overflow
+}
+
+impl Display for CongruenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CongruenceError::InvalidModulus { modulus } => writeln!(f, "modulus {} is not positive", modulus),
+            CongruenceError::Unsolvable { first, second } => writeln!(f, "no number satisfies both {} and {}", first, second),
+            CongruenceError::Overflow => writeln!(f, "solution is too large to represent")
+        }
+    }
+}
+
+impl Error for CongruenceError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_euclid_finds_bezout_coefficients() {
+        for (a, b) in [(240, 46), (46, 240), (17, 5), (12, 18), (7, 0), (-12, 18)] {
+            let (g, x, y) = extended_euclid(a, b);
+
+            assert_eq!(g, a * x + b * y);
+            assert!(g >= 0);
+        }
+
+        assert_eq!(2, gcd(240, 46));
+        assert_eq!(6, gcd(-12, 18));
+        assert_eq!(Some(36), lcm(12, 18));
+    }
+
+    #[test]
+    fn lcm_overflow_is_none() {
+        assert_eq!(None, lcm(i128::MAX, 2));
+        assert_eq!(Some(1 << 126), lcm(-(1 << 126), 2));
+        assert_eq!(Some(i128::MAX), lcm(i128::MAX, 1));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "i128::MIN")]
+    fn extended_euclid_rejects_min() {
+        extended_euclid(i128::MIN, 0);
+    }
+
+    #[test]
+    fn coprime_moduli() {
+        let congruences = [
+            Congruence::new(2, 3).unwrap(),
+            Congruence::new(3, 5).unwrap(),
+            Congruence::new(2, 7).unwrap()
+        ];
+
+        assert_eq!(Ok(Congruence { residue: 23, modulus: 105 }), solve(&congruences));
+    }
+
+    #[test]
+    fn non_coprime_moduli() {
+        let congruences = [
+            Congruence::new(0, 4).unwrap(),
+            Congruence::new(4, 6).unwrap()
+        ];
+
+        assert_eq!(Ok(Congruence { residue: 4, modulus: 12 }), solve(&congruences));
+    }
+
+    #[test]
+    fn unsolvable_system() {
+        let first = Congruence::new(0, 4).unwrap();
+        let second = Congruence::new(1, 6).unwrap();
+
+        assert_eq!(Err(CongruenceError::Unsolvable { first, second }), solve(&[first, second]));
+    }
+
+    #[test]
+    fn invalid_modulus() {
+        assert_eq!(Err(CongruenceError::InvalidModulus { modulus: 0 }), Congruence::new(1, 0));
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let first = Congruence::new(1, 1 << 126).unwrap();
+        let second = Congruence::new(0, 3).unwrap();
+
+        assert_eq!(Err(CongruenceError::Overflow), first.merge(&second));
+    }
+}
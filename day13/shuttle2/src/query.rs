@@ -0,0 +1,290 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::number_theory::{self, Congruence, CongruenceError};
+use crate::schedule::Schedule;
+
+// Bus `bus` leaving at `time`. Every bus leaves at each multiple of its ID.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Departure {
+    pub time: i128,
+    pub bus: i64
+}
+
+impl Display for Departure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} bus {}", self.time, self.bus)
+    }
+}
+
+// Departures of every bus in the schedule from `after` on, in time order.
+// Buses leaving at the same time are ordered by ID. A bus leaving exactly at
+// `after` counts, since there's no wait for it.
+pub struct Departures {
+    queue: BinaryHeap<Reverse<(i128, i64)>>
+}
+
+impl Departures {
+    pub fn new(schedule: &Schedule, after: i128) -> Result<Departures, QueryError> {
+        let mut queue = BinaryHeap::new();
+        let mut buses = 0;
+
+        for bus in schedule.bus_ids() {
+            if bus <= 0 {
+                return Err(QueryError::InvalidBus(bus));
+            }
+
+            // A bus whose first departure is past i128::MAX never leaves, the
+            // same as one that runs past it in next.
+            let wait = (bus as i128 - after.rem_euclid(bus as i128)) % bus as i128;
+            if let Some(first) = after.checked_add(wait) {
+                queue.push(Reverse((first, bus)));
+            }
+
+            buses += 1;
+        }
+
+        if buses > 0 && queue.is_empty() {
+            return Err(QueryError::TimeOutOfRange(after));
+        }
+
+        Ok(Departures { queue })
+    }
+}
+
+impl Iterator for Departures {
+    type Item = Departure;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((time, bus)) = self.queue.pop()?;
+
+        if let Some(next_time) = time.checked_add(bus as i128) {
+            self.queue.push(Reverse((next_time, bus)));
+        }
+
+        Some(Departure { time, bus })
+    }
+}
+
+pub fn next_departures(schedule: &Schedule, after: i128, count: usize) -> Result<Vec<Departure>, QueryError> {
+    Ok(Departures::new(schedule, after)?.take(count).collect())
+}
+
+// A bus that should leave `offset` minutes after the aligned time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    pub bus: i64,
+    pub offset: i128
+}
+
+impl FromStr for Alignment {
+    type Err = ParseAlignmentError;
+
+    // BUS:OFFSET, e.g. 13:1 for bus 13 leaving one minute after the others.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseAlignmentError { alignment: String::from(s) };
+
+        let (bus, offset) = s.split_once(':').ok_or_else(invalid)?;
+        let bus = bus.parse::<i64>().map_err(|_| invalid())?;
+        let offset = offset.parse::<i128>().map_err(|_| invalid())?;
+
+        Ok(Alignment { bus, offset })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseAlignmentError {
+    alignment: String
+}
+
+impl Display for ParseAlignmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "'{}' is not a valid alignment, expected BUS:OFFSET", self.alignment)
+    }
+}
+
+impl Error for ParseAlignmentError { }
+
+// The times in from..=to at which every aligned bus leaves at its offset.
+// They're found by solving the alignments once and stepping by the period of
+// the solution, so a wide window costs nothing until it's iterated.
+pub struct AlignedTimes {
+    next: Option<i128>,
+    step: i128,
+    to: i128
+}
+
+impl AlignedTimes {
+    pub fn new(schedule: &Schedule, alignments: &[Alignment], from: i128, to: i128) -> Result<AlignedTimes, QueryError> {
+        let mut congruences = Vec::new();
+
+        for alignment in alignments {
+            if !schedule.bus_ids().any(|bus| bus == alignment.bus) {
+                return Err(QueryError::UnknownBus(alignment.bus));
+            }
+
+            congruences.push(Congruence::new(-alignment.offset, alignment.bus as i128)?);
+        }
+
+        let solution = number_theory::solve(&congruences)?;
+        // Both residues are in 0..modulus, so their difference can't overflow.
+        // An aligned time past i128::MAX just leaves the window empty.
+        let wait = (solution.residue - from.rem_euclid(solution.modulus)).rem_euclid(solution.modulus);
+        let next = from.checked_add(wait);
+
+        Ok(AlignedTimes { next, step: solution.modulus, to })
+    }
+}
+
+impl Iterator for AlignedTimes {
+    type Item = i128;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let time = self.next.filter(|time| *time <= self.to)?;
+        self.next = time.checked_add(self.step);
+
+        Some(time)
+    }
+}
+
+// How long until every bus in the schedule leaves at the same times again.
+pub fn period(schedule: &Schedule) -> Result<i128, QueryError> {
+    schedule.bus_ids().try_fold(1, |accum, bus| {
+        if bus <= 0 {
+            return Err(QueryError::InvalidBus(bus));
+        }
+
+        number_theory::lcm(accum, bus as i128).ok_or(QueryError::Congruence(CongruenceError::Overflow))
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub enum QueryError {
+    InvalidBus(i64),
+    UnknownBus(i64),
+    TimeOutOfRange(i128),
+    Congruence(CongruenceError)
+}
+
+impl From<CongruenceError> for QueryError {
+    fn from(error: CongruenceError) -> Self {
+        QueryError::Congruence(error)
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::InvalidBus(bus) => writeln!(f, "bus {} never leaves", bus),
+            QueryError::UnknownBus(bus) => writeln!(f, "bus {} isn't in the schedule", bus),
+            QueryError::TimeOutOfRange(time) => writeln!(f, "departures after {} are too late to represent", time),
+            QueryError::Congruence(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl Error for QueryError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> Schedule {
+        "7,13,x,x,59,x,31,19".parse().unwrap()
+    }
+
+    #[test]
+    fn next_departure_answers_part_one() {
+        let departures = next_departures(&schedule(), 939, 1).unwrap();
+
+        assert_eq!(vec![Departure { time: 944, bus: 59 }], departures);
+    }
+
+    #[test]
+    fn departures_are_in_time_order() {
+        let schedule = "2,x,3".parse::<Schedule>().unwrap();
+        let times: Vec<(i128, i64)> = next_departures(&schedule, 5, 5).unwrap()
+            .into_iter()
+            .map(|departure| (departure.time, departure.bus))
+            .collect();
+
+        assert_eq!(vec![(6, 2), (6, 3), (8, 2), (9, 3), (10, 2)], times);
+    }
+
+    #[test]
+    fn departures_at_the_ends_of_time() {
+        let schedule = "2,x,3".parse::<Schedule>().unwrap();
+        let times: Vec<(i128, i64)> = next_departures(&schedule, i128::MIN, 3).unwrap()
+            .into_iter()
+            .map(|departure| (departure.time, departure.bus))
+            .collect();
+
+        assert_eq!(vec![(i128::MIN, 2), (i128::MIN + 2, 2), (i128::MIN + 2, 3)], times);
+        assert_eq!(Err(QueryError::TimeOutOfRange(i128::MAX)), next_departures(&schedule, i128::MAX, 1));
+
+        // i128::MAX - 1 is even, but the next multiple of 5 is too late.
+        let schedule = "2,x,5".parse::<Schedule>().unwrap();
+        let times: Vec<(i128, i64)> = next_departures(&schedule, i128::MAX - 1, 3).unwrap()
+            .into_iter()
+            .map(|departure| (departure.time, departure.bus))
+            .collect();
+
+        assert_eq!(vec![(i128::MAX - 1, 2)], times);
+    }
+
+    #[test]
+    fn aligned_times_in_window() {
+        let alignments = ["7:0".parse().unwrap(), "13:1".parse().unwrap()];
+        let times: Vec<i128> = AlignedTimes::new(&schedule(), &alignments, 0, 300).unwrap().collect();
+
+        assert_eq!(vec![77, 168, 259], times);
+    }
+
+    #[test]
+    fn aligned_times_at_the_ends_of_time() {
+        let alignments = ["7:0".parse().unwrap(), "13:1".parse().unwrap()];
+        let times: Vec<i128> = AlignedTimes::new(&schedule(), &alignments, i128::MIN, i128::MIN + 200).unwrap().collect();
+
+        assert_eq!(2, times.len());
+        assert!(times.iter().all(|time| time.rem_euclid(7) == 0 && (time + 1).rem_euclid(13) == 0));
+
+        let times: Vec<i128> = AlignedTimes::new(&schedule(), &alignments, i128::MAX - 10, i128::MAX).unwrap().collect();
+
+        assert!(times.is_empty());
+    }
+
+    #[test]
+    fn full_alignment_matches_earliest_time() {
+        let schedule = schedule();
+        let alignments: Vec<Alignment> = ["7:0", "13:1", "59:4", "31:6", "19:7"].iter()
+            .map(|alignment| alignment.parse().unwrap())
+            .collect();
+
+        let mut times = AlignedTimes::new(&schedule, &alignments, 0, i128::MAX).unwrap();
+
+        assert_eq!(Some(1068781), times.next());
+        assert_eq!(Some(1068781 + period(&schedule).unwrap()), times.next());
+    }
+
+    #[test]
+    fn unknown_bus_is_rejected() {
+        let alignments = ["11:0".parse().unwrap()];
+
+        assert!(matches!(AlignedTimes::new(&schedule(), &alignments, 0, 100), Err(QueryError::UnknownBus(11))));
+    }
+
+    #[test]
+    fn period_is_lcm_of_bus_ids() {
+        assert_eq!(Ok(7 * 13 * 59 * 31 * 19), period(&schedule()));
+        assert_eq!(Ok(12), period(&"4,x,6".parse().unwrap()));
+    }
+
+    #[test]
+    fn invalid_alignment() {
+        assert!("13".parse::<Alignment>().is_err());
+        assert!("x:1".parse::<Alignment>().is_err());
+    }
+}
@@ -2,85 +2,51 @@ use std::error::Error;
 use std::fmt::Display;
 use std::str::FromStr;
 
+use crate::number_theory::{self, Congruence, CongruenceError};
+
 #[derive(Debug)]
 pub struct Schedule {
     buses: Vec<BusId>
 }
 
 impl Schedule {
-    /*
-    Implementation of Chinese remainder theorem translated from Python as described here:
-        https://shainer.github.io/crypto/math/2017/10/22/chinese-remainder-theorem.html
+    // The IDs of the buses in service, in schedule order.
+    pub fn bus_ids(&self) -> impl Iterator<Item = i64> + '_ {
+        self.buses.iter().filter_map(|bus| match bus {
+            BusId::Specific(bus_id) => Some(*bus_id),
+            BusId::Any => None
+        })
+    }
 
-    Input:
+    /*
+    Each bus that has to leave i minutes after the earliest time t gives a
+    congruence, t = -i (mod bus). For:
     7,13,x,x,59,x,31,19
-    
-    Translates to:
-    x = 0 (mod 7)   // index = 0
-    x = 12 (mod 13) // index = 1, 13 - 1 = 12
-    x = 55 (mod 59) // index = 4, 59 - 4 = 55
-    x = 25 (mod 31) // index = 6, 31 - 6 = 25
-    x = 12 (mod 19) // index = 7, 19 - 7 = 12
-
-    Below algorithm gives result:
-    1068781
-    */
-    pub fn find_earliest_time(&self) -> i64 {
-        let product = self.buses.iter().fold(1, |accum, bus| match bus { 
-            BusId::Specific(bus_id) => accum * bus_id,
-            _ => accum
-        });
 
-        let mut result = 0;
+    That's:
+    t = 0 (mod 7)   // index = 0
+    t = 12 (mod 13) // index = 1, 13 - 1 = 12
+    t = 55 (mod 59) // index = 4, 59 - 4 = 55
+    t = 25 (mod 31) // index = 6, 31 - 6 = 25
+    t = 12 (mod 19) // index = 7, 19 - 7 = 12
 
-        for (i, bus) in self.buses.iter().enumerate() {
-            if let BusId::Specific(bus_id) = bus {
-                let ai = (bus_id - (i as i64)) % bus_id;
-                let ni = *bus_id;
-
-                println!("x = {} (mod {})", ai, ni);
-    
-                let (_, _, si) = Schedule::extended_euclid(ni, product / ni);
-                result = result + ai * si * (product / ni);
-            }            
-        }
-
-        // Algorithm can give negative number, need minimum positive answer for problem
-        if result > 0 {
-            result % product
-        } else {
-            product + (result % product)
-        }
+    Solving them together gives 1068781. Bus IDs needn't be coprime, but if two
+    of them share a factor their offsets have to agree or no time works.
+    */
+    pub fn congruences(&self) -> Result<Vec<Congruence>, CongruenceError> {
+        self.buses.iter()
+            .enumerate()
+            .filter_map(|(i, bus)| match bus {
+                BusId::Specific(bus_id) => Some(Congruence::new(-(i as i128), *bus_id as i128)),
+                BusId::Any => None
+            })
+            .collect()
     }
 
-    fn extended_euclid(x: i64, y: i64) -> (i64, i64, i64) {
-        let mut q = 0;
-
-        let mut x = x;
-        let mut y = y;
-
-        let mut x0 = 1;
-        let mut x1 = 0;
-        let mut y0 = 0;
-        let mut y1 = 1;
-
-        while y > 0 {
-            q = ((x as f64) / (y as f64)).floor() as i64;
+    pub fn find_earliest_time(&self) -> Result<i128, CongruenceError> {
+        let solution = number_theory::solve(&self.congruences()?)?;
 
-            let swap = x;
-            x = y;
-            y = swap % y;
-
-            let swap = x0;
-            x0 = x1;
-            x1 = swap - q * x1;
-
-            let swap = y0;
-            y0 = y1;
-            y1 = swap - q * y1;
-        }
-
-        (q, x0, y0)
+        Ok(solution.residue)
     }
 }
 
@@ -133,4 +99,47 @@ impl Display for ParseBusIdError {
     }
 }
 
-impl Error for ParseBusIdError { }
\ No newline at end of file
+impl Error for ParseBusIdError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn earliest_time(schedule: &str) -> Result<i128, CongruenceError> {
+        schedule.parse::<Schedule>().unwrap().find_earliest_time()
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(Ok(1068781), earliest_time("7,13,x,x,59,x,31,19"));
+        assert_eq!(Ok(3417), earliest_time("17,x,13,19"));
+        assert_eq!(Ok(754018), earliest_time("67,7,59,61"));
+        assert_eq!(Ok(779210), earliest_time("67,x,7,59,61"));
+        assert_eq!(Ok(1261476), earliest_time("67,7,x,59,61"));
+        assert_eq!(Ok(1202161486), earliest_time("1789,37,47,1889"));
+    }
+
+    #[test]
+    fn non_coprime_bus_ids() {
+        // t = 0 (mod 4) and t = 4 (mod 6).
+        assert_eq!(Ok(4), earliest_time("4,x,6"));
+    }
+
+    #[test]
+    fn no_time_aligns() {
+        // t has to be even for bus 4 and odd for bus 6.
+        assert!(matches!(earliest_time("4,6"), Err(CongruenceError::Unsolvable { .. })));
+    }
+
+    #[test]
+    fn large_schedule_does_not_overflow() {
+        // The product of these is well past i64::MAX.
+        let time = earliest_time("1000000007,x,998244353,1000000009,x,x,999999937").unwrap();
+
+        assert!(time > i64::MAX as i128 / 1000);
+        assert_eq!(0, time % 1000000007);
+        assert_eq!(0, (time + 2) % 998244353);
+        assert_eq!(0, (time + 3) % 1000000009);
+        assert_eq!(0, (time + 6) % 999999937);
+    }
+}
@@ -1,20 +0,0 @@
-use std::env;
-use std::process;
-
-use template::{run, config::Config};
-
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    
-    let config = Config::new(&args).unwrap_or_else(|err| {
-        println!("Problem parsing arguments: {}", err);
-        process::exit(1);
-    });
-    
-    let result = run(config).unwrap_or_else(|err| {
-        println!("Application error: {}", err);
-        process::exit(1);
-    });
-    
-    println!("{}", result);
-}
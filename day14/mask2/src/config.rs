@@ -1,33 +1,89 @@
 use std::error::Error;
 use std::fmt::Display;
 
+use crate::dump::{DumpFormat, ParseDumpFormatError};
+use crate::program::{Decoder, ParseDecoderError, DEFAULT_WIDTH, MAX_WIDTH};
+
 pub struct Config {
-    pub filename: String
+    pub filename: String,
+    pub decoder: Decoder,
+    pub width: u32,
+    pub dump: Option<String>,
+    pub format: DumpFormat,
+    pub history: Vec<u64>
 }
 
 impl Config {
+    // mask <file> [--decoder v1|v2] [--width N]
+    //     [--dump FILE [--format text|csv|hex]] [--history ADDRESS]...
+    //
+    // --width is the word size in bits, which every mask in the program has to
+    // match. It defaults to 36. --dump writes the final memory to FILE, and
+    // each --history lists the writes that touched that address, along with
+    // the mask each was made under.
     pub fn new(args: &[String]) -> Result<Config, ParseConfigError> {
         if args.len() < 2 {
             return Err(ParseConfigError::NotEnoughArguments);
         }
 
         let filename = args[1].clone();
-        
-        Ok(Config { filename })
+        let mut decoder = Decoder::V2;
+        let mut width = DEFAULT_WIDTH;
+        let mut dump = None;
+        let mut format = DumpFormat::Text;
+        let mut history = Vec::new();
+
+        let mut options = args[2..].iter();
+        while let Some(option) = options.next() {
+            match option.as_str() {
+                "--decoder" => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    decoder = value.parse().map_err(ParseConfigError::InvalidDecoder)?;
+                },
+                "--width" => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    width = value.parse::<u32>().ok()
+                        .filter(|width| *width > 0 && *width <= MAX_WIDTH)
+                        .ok_or(ParseConfigError::InvalidWidth)?;
+                },
+                "--dump" => { dump = Some(options.next().ok_or(ParseConfigError::NotEnoughArguments)?.clone()); },
+                "--format" => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    format = value.parse().map_err(ParseConfigError::InvalidFormat)?;
+                },
+                "--history" => {
+                    let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                    history.push(value.parse().map_err(|_| ParseConfigError::InvalidAddress(value.clone()))?);
+                },
+                _ => { return Err(ParseConfigError::UnknownOption(option.clone())); }
+            }
+        }
+
+        Ok(Config { filename, decoder, width, dump, format, history })
     }
 }
 
 #[derive(Debug)]
 pub enum ParseConfigError {
-    NotEnoughArguments
+    NotEnoughArguments,
+    UnknownOption(String),
+    InvalidDecoder(ParseDecoderError),
+    InvalidWidth,
+    InvalidFormat(ParseDumpFormatError),
+    InvalidAddress(String)
 }
 
 impl Display for ParseConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            ParseConfigError::NotEnoughArguments => "not enough arguments"
-        })
+        match self {
+            ParseConfigError::NotEnoughArguments => writeln!(f, "not enough arguments"),
+            ParseConfigError::UnknownOption(option) => writeln!(f, "unknown option '{}'", option),
+            ParseConfigError::InvalidDecoder(error) => write!(f, "{}", error),
+            ParseConfigError::InvalidWidth => writeln!(f, "width must be between 1 and {}", MAX_WIDTH),
+            ParseConfigError::InvalidFormat(error) => write!(f, "{}", error),
+            ParseConfigError::InvalidAddress(address) => writeln!(f, "'{}' is not a valid address", address)
+        }
     }
 }
 
-impl Error for ParseConfigError { }
\ No newline at end of file
+impl Error for ParseConfigError { }
@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::memory::Memory;
+
+// Floating writes can cover far more addresses than are worth listing, so a
+// dump refuses to expand memory past this many addresses.
+pub const MAX_DUMP_ADDRESSES: u128 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DumpFormat {
+    // "address: value", in decimal.
+    Text,
+    // An address,value header followed by one row per address.
+    Csv,
+    // "address: value" in zero-padded hex, as wide as the word.
+    Hex
+}
+
+impl FromStr for DumpFormat {
+    type Err = ParseDumpFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(DumpFormat::Text),
+            "csv" => Ok(DumpFormat::Csv),
+            "hex" => Ok(DumpFormat::Hex),
+            _ => Err(ParseDumpFormatError { name: String::from(s) })
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseDumpFormatError {
+    name: String
+}
+
+impl Display for ParseDumpFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "unknown dump format '{}', expected text, csv or hex", self.name)
+    }
+}
+
+impl Error for ParseDumpFormatError { }
+
+// Every written address and its final value, sorted by address. `width` is the
+// word size in bits, which sets the padding of hex dumps.
+pub fn dump(memory: &Memory, format: DumpFormat, width: u32) -> Result<String, DumpError> {
+    let count: u128 = memory.regions().iter().map(|(region, _)| region.count()).sum();
+    if count > MAX_DUMP_ADDRESSES {
+        return Err(DumpError::TooManyAddresses { count });
+    }
+
+    let mut cells: Vec<(u64, u64)> = memory.regions().iter()
+        .flat_map(|(region, value)| region.addresses().map(move |address| (address, *value)))
+        .collect();
+    cells.sort_unstable();
+
+    let digits = width.div_ceil(4) as usize;
+    let mut output = String::new();
+
+    if format == DumpFormat::Csv {
+        output.push_str("address,value\n");
+    }
+
+    for (address, value) in cells {
+        let line = match format {
+            DumpFormat::Text => format!("{}: {}\n", address, value),
+            DumpFormat::Csv => format!("{},{}\n", address, value),
+            DumpFormat::Hex => format!("{:0width$x}: {:0width$x}\n", address, value, width = digits)
+        };
+
+        output.push_str(&line);
+    }
+
+    Ok(output)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DumpError {
+    TooManyAddresses { count: u128 }
+}
+
+impl Display for DumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DumpError::TooManyAddresses { count } => writeln!(f, "memory has {} addresses, more than the {} a dump can list", count, MAX_DUMP_ADDRESSES)
+        }
+    }
+}
+
+impl Error for DumpError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::AddressPattern;
+
+    fn memory() -> Memory {
+        let mut memory = Memory::new();
+        memory.write(AddressPattern::new(0b1010, 0b0001), 100);
+        memory.write(AddressPattern::new(3, 0), 7);
+        memory.write(AddressPattern::new(11, 0), 255);
+
+        memory
+    }
+
+    #[test]
+    fn dumps_are_sorted_by_address() {
+        assert_eq!("3: 7\n10: 100\n11: 255\n", dump(&memory(), DumpFormat::Text, 36).unwrap());
+        assert_eq!("address,value\n3,7\n10,100\n11,255\n", dump(&memory(), DumpFormat::Csv, 36).unwrap());
+        assert_eq!("000000003: 000000007\n00000000a: 000000064\n00000000b: 0000000ff\n", dump(&memory(), DumpFormat::Hex, 36).unwrap());
+    }
+
+    #[test]
+    fn huge_memory_is_not_dumped() {
+        let mut memory = Memory::new();
+        memory.write(AddressPattern::new(0, (1 << 36) - 1), 1);
+
+        assert_eq!(Err(DumpError::TooManyAddresses { count: 1 << 36 }), dump(&memory, DumpFormat::Text, 36));
+    }
+}
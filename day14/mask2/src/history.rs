@@ -0,0 +1,100 @@
+use std::fmt::Display;
+
+use crate::memory::AddressPattern;
+
+// The mask a write was made under, kept as written so it can be shown the
+// same way it appears in the program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaskRecord {
+    pub high_mask: u64,
+    pub floating_mask: u64,
+    pub width: u32
+}
+
+impl Display for MaskRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for i in (0..self.width).rev() {
+            let c = if self.floating_mask >> i & 1 == 1 {
+                'X'
+            } else if self.high_mask >> i & 1 == 1 {
+                '1'
+            } else {
+                '0'
+            };
+
+            write!(f, "{}", c)?;
+        }
+
+        Ok(())
+    }
+}
+
+// One mem instruction as the interpreter carried it out: what the program
+// asked for, and the addresses and value that were actually stored once the
+// mask was applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Write {
+    pub line: usize,
+    pub address: u64,
+    pub value: u64,
+    pub mask: MaskRecord,
+    pub pattern: AddressPattern,
+    pub stored: u64
+}
+
+impl Display for Write {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: mem[{}] = {}, mask {}, stored {}", self.line, self.address, self.value, self.mask, self.stored)
+    }
+}
+
+// Every write in the order the program made them.
+#[derive(Debug, Default)]
+pub struct History {
+    writes: Vec<Write>
+}
+
+impl History {
+    pub fn new() -> History {
+        History { writes: Vec::new() }
+    }
+
+    pub fn record(&mut self, write: Write) {
+        self.writes.push(write);
+    }
+
+    pub fn writes(&self) -> &[Write] {
+        &self.writes
+    }
+
+    // The writes that touched `address`, oldest first. The last one is the
+    // value it ends up holding.
+    pub fn for_address(&self, address: u64) -> impl Iterator<Item = &Write> + '_ {
+        self.writes.iter().filter(move |write| write.pattern.contains(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_is_shown_at_full_width() {
+        let mask = MaskRecord { high_mask: 0b0100, floating_mask: 0b0001, width: 6 };
+
+        assert_eq!("00010X", mask.to_string());
+    }
+
+    #[test]
+    fn history_filters_by_address() {
+        let mask = MaskRecord { high_mask: 0, floating_mask: 0b1, width: 4 };
+        let mut history = History::new();
+        history.record(Write { line: 2, address: 4, value: 1, mask, pattern: AddressPattern::new(4, 0b1), stored: 1 });
+        history.record(Write { line: 3, address: 6, value: 2, mask, pattern: AddressPattern::new(6, 0b1), stored: 2 });
+
+        let lines: Vec<usize> = history.for_address(5).map(|write| write.line).collect();
+        assert_eq!(vec![2], lines);
+        assert_eq!(0, history.for_address(8).count());
+        assert_eq!("line 3: mem[6] = 2, mask 000X, stored 2", history.writes()[1].to_string());
+    }
+}
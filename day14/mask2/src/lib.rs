@@ -1,6 +1,5 @@
 use std::error::Error;
-use std::fmt::Display;
-use std::fs::{File};
+use std::fs::{self, File};
 use std::io::{self, BufRead};
 use std::path::Path;
 
@@ -8,10 +7,13 @@ use config::Config;
 use program::{Instruction, Program};
 
 pub mod config;
-mod program;
+pub mod dump;
+pub mod history;
+pub mod memory;
+pub mod program;
 
-pub fn run(config: Config) -> Result<u64, Box<dyn Error>> {
-    let filename = config.filename;
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
+    let filename = &config.filename;
 
     let mut instructions = Vec::new();
 
@@ -21,11 +23,23 @@ pub fn run(config: Config) -> Result<u64, Box<dyn Error>> {
         instructions.push(instruction);
     }
 
-    let program = Program::new(instructions);
+    let program = Program::new(instructions, config.decoder, config.width)?;
     
-    let sum = program.execute()?;
+    let (memory, history) = program.trace()?;
 
-    Ok(sum)
+    if let Some(path) = &config.dump {
+        fs::write(path, dump::dump(&memory, config.format, config.width)?)?;
+    }
+
+    let mut output = Vec::new();
+    for address in config.history.iter() {
+        output.push(format!("mem[{}]:", address));
+        output.extend(history.for_address(*address).map(|write| format!("  {}", write)));
+    }
+
+    output.push(memory.sum().to_string());
+
+    Ok(output.join("\n"))
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
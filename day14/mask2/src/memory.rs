@@ -0,0 +1,233 @@
+use std::fmt::Display;
+
+// A set of addresses: every address whose non-floating bits equal `fixed`,
+// whatever its floating bits are. `fixed` is kept zero at floating bits, so
+// two patterns are equal exactly when they cover the same addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AddressPattern {
+    fixed: u64,
+    floating: u64
+}
+
+impl AddressPattern {
+    pub fn new(fixed: u64, floating: u64) -> AddressPattern {
+        AddressPattern { fixed: fixed & !floating, floating }
+    }
+
+    pub fn fixed(&self) -> u64 {
+        self.fixed
+    }
+
+    pub fn floating(&self) -> u64 {
+        self.floating
+    }
+
+    // How many addresses are covered.
+    pub fn count(&self) -> u128 {
+        1 << self.floating.count_ones()
+    }
+
+    pub fn contains(&self, address: u64) -> bool {
+        address & !self.floating == self.fixed
+    }
+
+    // The addresses both patterns cover, if there are any. They overlap unless
+    // a bit is fixed in both with different values.
+    pub fn intersect(&self, other: &AddressPattern) -> Option<AddressPattern> {
+        let both_fixed = !self.floating & !other.floating;
+        if (self.fixed ^ other.fixed) & both_fixed != 0 {
+            return None;
+        }
+
+        Some(AddressPattern::new(self.fixed | other.fixed, self.floating & other.floating))
+    }
+
+    // The addresses this pattern covers that `other` doesn't, split into
+    // disjoint patterns. Each bit that floats here but is fixed in `other`
+    // gives one piece: that bit takes the value `other` doesn't have, and the
+    // bits handled before it take the value it does. Whatever's left over
+    // agrees with `other` on every such bit, so it's inside `other`.
+    pub fn subtract(&self, other: &AddressPattern) -> Vec<AddressPattern> {
+        if self.intersect(other).is_none() {
+            return vec![*self];
+        }
+
+        let mut pieces = Vec::new();
+        let mut fixed = self.fixed;
+        let mut floating = self.floating;
+
+        let mut splitting = self.floating & !other.floating;
+        while splitting != 0 {
+            let bit = splitting & splitting.wrapping_neg();
+            splitting &= !bit;
+            floating &= !bit;
+
+            pieces.push(AddressPattern::new(fixed | (!other.fixed & bit), floating));
+            fixed |= other.fixed & bit;
+        }
+
+        pieces
+    }
+
+    // Every address covered, in ascending order. This is 2^n addresses for n
+    // floating bits, so it's only meant for small patterns.
+    pub fn addresses(&self) -> impl Iterator<Item = u64> + '_ {
+        let count = self.count();
+
+        (0..count).map(move |n| self.fixed | deposit(n as u64, self.floating))
+    }
+}
+
+impl Display for AddressPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = 64 - (self.fixed | self.floating).leading_zeros().min(63) as usize;
+
+        for i in (0..width).rev() {
+            let c = if self.floating >> i & 1 == 1 {
+                'X'
+            } else if self.fixed >> i & 1 == 1 {
+                '1'
+            } else {
+                '0'
+            };
+
+            write!(f, "{}", c)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Spreads the low bits of `value` out over the set bits of `mask`, lowest
+// first.
+fn deposit(value: u64, mask: u64) -> u64 {
+    let mut result = 0;
+    let mut mask = mask;
+    let mut value = value;
+
+    while mask != 0 {
+        let bit = mask & mask.wrapping_neg();
+        if value & 1 == 1 {
+            result |= bit;
+        }
+
+        mask &= !bit;
+        value >>= 1;
+    }
+
+    result
+}
+
+// Memory as disjoint address patterns, each holding one value. A write takes
+// its addresses out of every earlier pattern and adds a pattern of its own, so
+// nothing is ever expanded into single addresses.
+#[derive(Debug, Default)]
+pub struct Memory {
+    regions: Vec<(AddressPattern, u64)>
+}
+
+impl Memory {
+    pub fn new() -> Memory {
+        Memory { regions: Vec::new() }
+    }
+
+    pub fn write(&mut self, pattern: AddressPattern, value: u64) {
+        let mut regions = Vec::with_capacity(self.regions.len() + 1);
+
+        for (region, existing) in self.regions.iter() {
+            for piece in region.subtract(&pattern) {
+                regions.push((piece, *existing));
+            }
+        }
+
+        regions.push((pattern, value));
+
+        self.regions = regions;
+    }
+
+    pub fn regions(&self) -> &[(AddressPattern, u64)] {
+        &self.regions
+    }
+
+    pub fn get(&self, address: u64) -> u64 {
+        self.regions.iter()
+            .find(|(region, _)| region.contains(address))
+            .map_or(0, |(_, value)| *value)
+    }
+
+    // Sum of the values at every address. A u128, since a pattern can cover
+    // more addresses than a u64 sum of 36-bit values allows for.
+    pub fn sum(&self) -> u128 {
+        self.regions.iter()
+            .map(|(region, value)| region.count() * *value as u128)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(s: &str) -> AddressPattern {
+        let fixed = u64::from_str_radix(&s.replace('X', "0"), 2).unwrap();
+        let floating = u64::from_str_radix(&s.replace('1', "0").replace('X', "1"), 2).unwrap();
+
+        AddressPattern::new(fixed, floating)
+    }
+
+    #[test]
+    fn addresses_are_ascending() {
+        let addresses: Vec<u64> = pattern("X1101X").addresses().collect();
+
+        assert_eq!(vec![26, 27, 58, 59], addresses);
+        assert_eq!(4, pattern("X1101X").count());
+    }
+
+    #[test]
+    fn intersect() {
+        assert_eq!(Some(pattern("1X01")), pattern("1XX1").intersect(&pattern("XX01")));
+        assert_eq!(None, pattern("1XX1").intersect(&pattern("0XXX")));
+    }
+
+    #[test]
+    fn subtract_leaves_disjoint_pieces() {
+        let a = pattern("XXXX");
+        let b = pattern("1X0X");
+        let pieces = a.subtract(&b);
+
+        let mut addresses: Vec<u64> = pieces.iter().flat_map(|piece| piece.addresses()).collect();
+        addresses.sort_unstable();
+
+        let expected: Vec<u64> = a.addresses().filter(|address| !b.contains(*address)).collect();
+        assert_eq!(expected, addresses);
+        assert_eq!(12, pieces.iter().map(|piece| piece.count()).sum::<u128>());
+    }
+
+    #[test]
+    fn subtract_disjoint_and_covered() {
+        assert_eq!(vec![pattern("1XX")], pattern("1XX").subtract(&pattern("0XX")));
+        assert!(pattern("10X").subtract(&pattern("1XX")).is_empty());
+    }
+
+    #[test]
+    fn overlapping_writes() {
+        let mut memory = Memory::new();
+        memory.write(pattern("X1101X"), 100);
+        memory.write(pattern("1X0XX"), 1);
+
+        assert_eq!(208, memory.sum());
+        assert_eq!(1, memory.get(26));
+        assert_eq!(100, memory.get(58));
+        assert_eq!(0, memory.get(0));
+    }
+
+    #[test]
+    fn fully_floating_writes() {
+        let mut memory = Memory::new();
+        memory.write(AddressPattern::new(0, (1 << 36) - 1), 1 << 35);
+        memory.write(AddressPattern::new(1, (1 << 36) - 2), 1);
+
+        assert_eq!((1 << 70) + (1 << 35), memory.sum());
+        assert_eq!(2, memory.regions().len());
+    }
+}
@@ -1,4 +1,3 @@
-use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt::Display;
 use std::str::FromStr;
@@ -6,59 +5,154 @@ use std::str::FromStr;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use crate::history::{History, MaskRecord, Write};
+use crate::memory::{AddressPattern, Memory};
+
 // mask = 0010011010X1000100X101011X10010X1010
 // mem[57319] = 8001842
 // mem[29943] = 1246
 // mem[3087] = 1055661079
 
+pub const DEFAULT_WIDTH: u32 = 36;
+pub const MAX_WIDTH: u32 = 64;
+
+// How a mask is applied to a write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decoder {
+    // The mask overwrites bits of the value; X leaves them alone.
+    V1,
+    // The mask decodes the address; X makes the bit float.
+    V2
+}
+
+impl Display for Decoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Decoder::V1 => "v1",
+            Decoder::V2 => "v2"
+        })
+    }
+}
+
+impl FromStr for Decoder {
+    type Err = ParseDecoderError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" | "1" => Ok(Decoder::V1),
+            "v2" | "2" => Ok(Decoder::V2),
+            _ => Err(ParseDecoderError { name: String::from(s) })
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseDecoderError {
+    name: String
+}
+
+impl Display for ParseDecoderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "unknown decoder '{}', expected v1 or v2", self.name)
+    }
+}
+
+impl Error for ParseDecoderError { }
+
 pub struct Program {
-    instructions: Vec<Instruction>
+    instructions: Vec<Instruction>,
+    decoder: Decoder,
+    width: u32
 }
 
 impl Program {
-    pub fn new(instructions: Vec<Instruction>) -> Program {        
-        Program { instructions }
+    // Every mask has to be `width` bits long, and every address and value has
+    // to fit in that many bits.
+    pub fn new(instructions: Vec<Instruction>, decoder: Decoder, width: u32) -> Result<Program, ProgramError> {
+        if width == 0 || width > MAX_WIDTH {
+            return Err(ProgramError::InvalidWidth(width));
+        }
+
+        let limit = word_mask(width);
+        for instruction in instructions.iter() {
+            match instruction {
+                Instruction::Mask { width: mask_width, .. } => {
+                    if *mask_width != width {
+                        return Err(ProgramError::MaskWidth { expected: width, found: *mask_width });
+                    }
+                }
+                Instruction::Mem { address, value } => {
+                    if let Some(too_wide) = [*address, *value].iter().find(|n| **n & !limit != 0) {
+                        return Err(ProgramError::TooWide { value: *too_wide, width });
+                    }
+                }
+            }
+        }
+
+        Ok(Program { instructions, decoder, width })
     }
 
-    pub fn execute(&self) -> Result<u64, ProgramError> {
-        let mut memory: HashMap<u64, u64> = HashMap::new();
+    pub fn decoder(&self) -> Decoder {
+        self.decoder
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn execute(&self) -> Result<u128, ProgramError> {
+        let (memory, _) = self.trace()?;
+
+        Ok(memory.sum())
+    }
+
+    // Runs the program, keeping the final memory and a record of every write.
+    // Instructions are numbered from 1, one per line of the program.
+    pub fn trace(&self) -> Result<(Memory, History), ProgramError> {
+        let mut memory = Memory::new();
+        let mut history = History::new();
 
         let mut current_mask = None;
 
-        for instruction in self.instructions.iter() {
+        for (i, instruction) in self.instructions.iter().enumerate() {
             match instruction {
-                Instruction::Mask { high_mask, floating_mask } => {
-                    current_mask = Some((*high_mask, *floating_mask));
+                Instruction::Mask { high_mask, floating_mask, width } => {
+                    current_mask = Some(MaskRecord { high_mask: *high_mask, floating_mask: *floating_mask, width: *width });
                 }
                 Instruction::Mem { address, value } => {
-                    if let Some((high_mask, floating_mask)) = current_mask {
-                        let masked_addresses = apply_mask(*address, high_mask, floating_mask);
-                        for address in masked_addresses {
-                            memory.insert(address, *value);
-                        }
-                    } else {
-                        return Err(ProgramError::NoMaskSet);   
-                    }
+                    let mask = current_mask.ok_or(ProgramError::NoMaskSet)?;
+
+                    let (pattern, stored) = match self.decoder {
+                        Decoder::V1 => (AddressPattern::new(*address, 0), mask_value(*value, mask.high_mask, mask.floating_mask, self.width)),
+                        Decoder::V2 => (apply_mask(*address, mask.high_mask, mask.floating_mask), *value)
+                    };
+
+                    memory.write(pattern, stored);
+                    history.record(Write { line: i + 1, address: *address, value: *value, mask, pattern, stored });
                 }
             }
         }
 
-        let sum = memory.iter().fold(0, |accum, (_, v)| accum + v);
-
-        Ok(sum)
+        Ok((memory, history))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum ProgramError {
-    NoMaskSet
+    NoMaskSet,
+    InvalidWidth(u32),
+    MaskWidth { expected: u32, found: u32 },
+    TooWide { value: u64, width: u32 }
 }
 
 impl Display for ProgramError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "{}", match self {
-            ProgramError::NoMaskSet => "no mask set"
-        })
+        match self {
+            ProgramError::NoMaskSet => writeln!(f, "no mask set"),
+            ProgramError::InvalidWidth(width) => writeln!(f, "word width {} is not between 1 and {}", width, MAX_WIDTH),
+            ProgramError::MaskWidth { expected, found } => writeln!(f, "mask is {} bits wide, expected {}", found, expected),
+            ProgramError::TooWide { value, width } => writeln!(f, "{} doesn't fit in {} bits", value, width)
+        }
     }
 }
 
@@ -66,7 +160,7 @@ impl Error for ProgramError { }
 
 #[derive(Debug)]
 pub enum Instruction {
-    Mask { high_mask: u64, floating_mask: u64 },
+    Mask { high_mask: u64, floating_mask: u64, width: u32 },
     Mem { address: u64, value: u64 }
 }
 
@@ -75,10 +169,10 @@ impl FromStr for Instruction {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         static MASK_RE: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r"mask = ([0-9X]{36})").unwrap()
+            Regex::new(r"^mask = ([01X]+)$").unwrap()
         });
         static MEM_RE: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r"mem\[(\d+)\] = (\d+)").unwrap()
+            Regex::new(r"^mem\[(\d+)\] = (\d+)$").unwrap()
         });
 
         if let Some(capture) = MASK_RE.captures(s) {
@@ -86,81 +180,73 @@ impl FromStr for Instruction {
             let mut floating_mask: u64 = 0;
 
             let mask_str = &capture[1];
+            if mask_str.len() > MAX_WIDTH as usize {
+                return Err(ParseInstructionError::MaskTooWide);
+            }
+
             for (i, c) in mask_str.chars().enumerate() {
                 if i > 0 {
-                    high_mask = high_mask << 1;
-                    floating_mask = floating_mask << 1;
+                    high_mask <<= 1;
+                    floating_mask <<= 1;
                 }
 
                 if c == '1' {
-                    high_mask = high_mask | 1;
+                    high_mask |= 1;
                 } else if c == 'X' {
-                    floating_mask = floating_mask | 1;
+                    floating_mask |= 1;
                 }
             }
 
-            return Ok(Instruction::Mask { high_mask, floating_mask });
+            Ok(Instruction::Mask { high_mask, floating_mask, width: mask_str.len() as u32 })
         } else if let Some(capture) = MEM_RE.captures(s) {
             let address_str = &capture[1];
             let value_str = &capture[2];
 
-            let address = address_str.parse::<u64>().unwrap();
-            let value = value_str.parse::<u64>().unwrap();
+            let address = address_str.parse::<u64>().map_err(|_| ParseInstructionError::NumberTooLarge)?;
+            let value = value_str.parse::<u64>().map_err(|_| ParseInstructionError::NumberTooLarge)?;
 
-            return Ok(Instruction::Mem { address, value });
+            Ok(Instruction::Mem { address, value })
         } else {
-            return Err(ParseInstructionError::UnrecognizedCommand);
+            Err(ParseInstructionError::UnrecognizedCommand)
         }
     }
 }
 
 #[derive(Debug)]
 pub enum ParseInstructionError {
-    UnrecognizedCommand
+    UnrecognizedCommand,
+    MaskTooWide,
+    NumberTooLarge
 }
 
 impl Display for ParseInstructionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", match self {
-            ParseInstructionError::UnrecognizedCommand => "unrecognized command"
+            ParseInstructionError::UnrecognizedCommand => "unrecognized command",
+            ParseInstructionError::MaskTooWide => "mask is wider than 64 bits",
+            ParseInstructionError::NumberTooLarge => "address or value is wider than 64 bits"
         })
     }
  }
 
 impl Error for ParseInstructionError { }
 
-fn apply_mask(value: u64, high_mask: u64, floating_mask: u64) -> Vec<u64> {
-    let high_masked = value | high_mask;
-        
-    let mut result = HashSet::new();
-
-    result.insert(high_masked);
+// Ones and zeros in the mask overwrite the bit in the value, X bits leave it
+// alone.
+fn mask_value(value: u64, high_mask: u64, floating_mask: u64, width: u32) -> u64 {
+    let low_mask = word_mask(width) & !high_mask & !floating_mask;
 
-    for i in 0..36 {
-        let is_active = floating_mask >> i & 1 == 1;
-        if is_active {
-            let current_mask = 1 << i;
-            let inverted_mask = !current_mask;
-  
-            let mut temp = Vec::new();
-
-            for existing in result.iter() {                     
-                let on = existing | current_mask;
-                let off = existing & inverted_mask;
-
-                temp.push(on);
-                temp.push(off);
-            }
-
-            result.extend(temp.iter());
-
-        }
-    }
+    (value | high_mask) & !low_mask
+}
 
-    let mut result = result.into_iter().collect::<Vec<u64>>();
-    result.sort();
+fn word_mask(width: u32) -> u64 {
+    u64::MAX >> (MAX_WIDTH - width)
+}
 
-    result
+// Ones in the mask set the address bit, zeros leave it alone, and X bits float
+// so the write covers both values of each.
+fn apply_mask(address: u64, high_mask: u64, floating_mask: u64) -> AddressPattern {
+    AddressPattern::new(address | high_mask, floating_mask)
 }
 
 #[cfg(test)]
@@ -172,8 +258,8 @@ mod tests {
     #[test]
     fn example_mask_1() {
         let instruction = "mask = 000000000000000000000000000000X1001X".parse::<Instruction>().unwrap();
-        if let Instruction::Mask { high_mask, floating_mask } = instruction {
-            let result = apply_mask(42, high_mask, floating_mask);
+        if let Instruction::Mask { high_mask, floating_mask, .. } = instruction {
+            let result: Vec<u64> = apply_mask(42, high_mask, floating_mask).addresses().collect();
 
             assert_eq!(vec![26, 27, 58, 59], result);
         } else {
@@ -186,12 +272,93 @@ mod tests {
     #[test]
     fn example_mask_2() {
         let instruction = "mask = 00000000000000000000000000000000X0XX".parse::<Instruction>().unwrap();
-        if let Instruction::Mask { high_mask, floating_mask } = instruction {
-            let result = apply_mask(26, high_mask, floating_mask);
+        if let Instruction::Mask { high_mask, floating_mask, .. } = instruction {
+            let result: Vec<u64> = apply_mask(26, high_mask, floating_mask).addresses().collect();
 
             assert_eq!(vec![16, 17, 18, 19, 24, 25, 26, 27], result);
         } else {
             panic!("expected Mask, got {:?}", instruction);
         }
     }
-}
\ No newline at end of file
+
+    fn program(input: &str, decoder: Decoder, width: u32) -> Result<Program, ProgramError> {
+        Program::new(input.lines().map(|line| line.parse().unwrap()).collect(), decoder, width)
+    }
+
+    #[test]
+    fn example_program() {
+        let program = program("mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1", Decoder::V2, DEFAULT_WIDTH).unwrap();
+
+        assert_eq!(208, program.execute().unwrap());
+    }
+
+    #[test]
+    fn masks_full_of_floating_bits() {
+        let program = program("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+mem[0] = 3
+mask = 0000000000000000000000000000000000X1
+mem[0] = 1", Decoder::V2, DEFAULT_WIDTH).unwrap();
+
+        // Two addresses are overwritten with 1, the rest of the 2^36 keep 3.
+        assert_eq!(3 * ((1 << 36) - 2) + 2, program.execute().unwrap());
+    }
+
+    #[test]
+    fn example_program_v1() {
+        let program = program("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+mem[8] = 11
+mem[7] = 101
+mem[8] = 0", Decoder::V1, DEFAULT_WIDTH).unwrap();
+
+        assert_eq!(165, program.execute().unwrap());
+    }
+
+    #[test]
+    fn other_word_widths() {
+        // 4 bit words: 0111 masked with 10XX is 1011.
+        let narrow = program("mask = 10XX\nmem[3] = 7", Decoder::V1, 4).unwrap();
+        assert_eq!(11, narrow.execute().unwrap());
+
+        let mask = format!("mask = 1{}", "X".repeat(63));
+        let wide = program(&format!("{}\nmem[0] = 1", mask), Decoder::V2, 64).unwrap();
+        assert_eq!(1 << 63, wide.execute().unwrap());
+
+        let value = program(&format!("{}\nmem[0] = {}", mask, u64::MAX), Decoder::V1, 64).unwrap();
+        assert_eq!(u64::MAX as u128, value.execute().unwrap());
+    }
+
+    #[test]
+    fn width_is_checked() {
+        let input = "mask = 10XX\nmem[3] = 7";
+
+        assert_eq!(Some(ProgramError::MaskWidth { expected: 36, found: 4 }), program(input, Decoder::V1, 36).err());
+        assert_eq!(Some(ProgramError::InvalidWidth(65)), program(input, Decoder::V1, 65).err());
+        assert_eq!(Some(ProgramError::TooWide { value: 7, width: 2 }), program("mask = 1X\nmem[3] = 7", Decoder::V2, 2).err());
+        assert!(format!("mask = {}", "0".repeat(65)).parse::<Instruction>().is_err());
+    }
+
+    #[test]
+    fn trailing_garbage_is_rejected() {
+        for line in ["mask = 10X2X1", "mask = 10XX ", "mem[8] = 11x", "xmem[8] = 11", "mem[8] = 11 = 12"] {
+            assert!(matches!(line.parse::<Instruction>(), Err(ParseInstructionError::UnrecognizedCommand)), "{}", line);
+        }
+    }
+
+    #[test]
+    fn trace_records_every_write() {
+        let program = program("mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1", Decoder::V2, DEFAULT_WIDTH).unwrap();
+
+        let (memory, history) = program.trace().unwrap();
+        let lines: Vec<usize> = history.for_address(26).map(|write| write.line).collect();
+
+        assert_eq!(vec![2, 4], lines);
+        assert_eq!(1, memory.get(26));
+        assert_eq!("line 2: mem[42] = 100, mask 000000000000000000000000000000X1001X, stored 100", history.writes()[0].to_string());
+    }
+}
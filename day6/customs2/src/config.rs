@@ -0,0 +1,43 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::report::{ParseReportFormatError, ReportFormat};
+
+pub struct Config {
+    pub filename: String,
+    pub report: Option<ReportFormat>
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, ParseConfigError> {
+        if args.len() < 2 {
+            return Err(ParseConfigError::NotEnoughArguments);
+        }
+
+        let filename = args[1].clone();
+        let report = match args.get(2) {
+            Some(format) => Some(format.parse::<ReportFormat>()
+                .map_err(ParseConfigError::InvalidReportFormat)?),
+            None => None
+        };
+
+        Ok(Config { filename, report })
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseConfigError {
+    NotEnoughArguments,
+    InvalidReportFormat(ParseReportFormatError)
+}
+
+impl Display for ParseConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseConfigError::NotEnoughArguments => writeln!(f, "not enough arguments"),
+            ParseConfigError::InvalidReportFormat(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl Error for ParseConfigError { }
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, error::Error, fmt::Display, str::FromStr};
+use std::{collections::{BTreeMap, HashSet}, error::Error, fmt::Display, str::FromStr};
 
 pub struct Form {
     entries: Vec<FormEntry>
@@ -9,13 +9,13 @@ impl Form {
         let entries = Vec::new();
         Form { entries }
     }
-    
+
     pub fn insert(&mut self, entry: FormEntry) {
         self.entries.push(entry);
     }
 
     pub fn count(&self) -> usize {
-        if self.entries.len() == 0 {
+        if self.entries.is_empty() {
             return 0;
         }
 
@@ -30,11 +30,160 @@ impl Form {
         compiled_answers.len()
     }
 
+    pub fn group_size(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[FormEntry] {
+        &self.entries
+    }
+
+    pub fn question_counts(&self) -> BTreeMap<char, usize> {
+        let mut counts = BTreeMap::new();
+
+        for entry in self.entries.iter() {
+            for answer in entry.answers.iter() {
+                *counts.entry(*answer).or_insert(0) += 1;
+            }
+        }
+
+        counts
+    }
+
+    pub fn statistics(&self) -> FormStatistics {
+        FormStatistics::new(self.group_size(), self.question_counts())
+    }
+
     pub fn clear(&mut self) {
         self.entries.clear();
     }
 }
 
+impl Default for Form {
+    fn default() -> Self {
+        Form::new()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FormStatistics {
+    group_size: usize,
+    question_counts: BTreeMap<char, usize>
+}
+
+impl FormStatistics {
+    pub fn new(group_size: usize, question_counts: BTreeMap<char, usize>) -> FormStatistics {
+        FormStatistics { group_size, question_counts }
+    }
+
+    pub fn group_size(&self) -> usize {
+        self.group_size
+    }
+
+    pub fn question_counts(&self) -> &BTreeMap<char, usize> {
+        &self.question_counts
+    }
+
+    pub fn anyone_count(&self) -> usize {
+        self.question_counts.len()
+    }
+
+    pub fn everyone_count(&self) -> usize {
+        if self.group_size == 0 {
+            return 0;
+        }
+
+        self.question_counts.values()
+            .filter(|count| **count == self.group_size)
+            .count()
+    }
+
+    pub fn most_common(&self) -> Vec<char> {
+        match self.question_counts.values().max() {
+            Some(max) => self.answers_with_count(*max),
+            None => Vec::new()
+        }
+    }
+
+    pub fn least_common(&self) -> Vec<char> {
+        match self.question_counts.values().min() {
+            Some(min) => self.answers_with_count(*min),
+            None => Vec::new()
+        }
+    }
+
+    fn answers_with_count(&self, count: usize) -> Vec<char> {
+        self.question_counts.iter()
+            .filter(|(_, c)| **c == count)
+            .map(|(answer, _)| *answer)
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SurveyStatistics {
+    groups: Vec<FormStatistics>,
+    total: FormStatistics,
+    groups_by_question: BTreeMap<char, usize>,
+    group_size_histogram: BTreeMap<usize, usize>
+}
+
+impl SurveyStatistics {
+    pub fn new(forms: &[Form]) -> SurveyStatistics {
+        let groups: Vec<FormStatistics> = forms.iter()
+            .filter(|form| !form.is_empty())
+            .map(|form| form.statistics())
+            .collect();
+
+        let mut total_size = 0;
+        let mut total_counts = BTreeMap::new();
+        let mut groups_by_question = BTreeMap::new();
+        let mut group_size_histogram = BTreeMap::new();
+
+        for group in groups.iter() {
+            total_size += group.group_size;
+            *group_size_histogram.entry(group.group_size).or_insert(0) += 1;
+
+            for (answer, count) in group.question_counts.iter() {
+                *total_counts.entry(*answer).or_insert(0) += count;
+                *groups_by_question.entry(*answer).or_insert(0) += 1;
+            }
+        }
+
+        let total = FormStatistics::new(total_size, total_counts);
+
+        SurveyStatistics { groups, total, groups_by_question, group_size_histogram }
+    }
+
+    pub fn groups(&self) -> &[FormStatistics] {
+        &self.groups
+    }
+
+    pub fn total(&self) -> &FormStatistics {
+        &self.total
+    }
+
+    pub fn groups_by_question(&self) -> &BTreeMap<char, usize> {
+        &self.groups_by_question
+    }
+
+    pub fn group_size_histogram(&self) -> &BTreeMap<usize, usize> {
+        &self.group_size_histogram
+    }
+
+    pub fn anyone_sum(&self) -> usize {
+        self.groups.iter().map(|group| group.anyone_count()).sum()
+    }
+
+    pub fn everyone_sum(&self) -> usize {
+        self.groups.iter().map(|group| group.everyone_count()).sum()
+    }
+}
+
 #[derive(Debug)]
 pub struct FormEntry {
     answers: HashSet<char>
@@ -44,6 +193,14 @@ impl FormEntry {
     pub fn len(&self) -> usize {
         self.answers.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    pub fn contains(&self, answer: char) -> bool {
+        self.answers.contains(&answer)
+    }
 }
 
 impl FromStr for FormEntry {
@@ -67,6 +224,52 @@ impl Error for ParseFormEntryError { }
 
 impl Display for ParseFormEntryError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!();
+        writeln!(f, "couldn't parse form entry")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn form(entries: &[&str]) -> Form {
+        let mut form = Form::new();
+        for entry in entries {
+            form.insert(entry.parse::<FormEntry>().unwrap());
+        }
+
+        form
+    }
+
+    #[test]
+    fn form_statistics() {
+        let statistics = form(&["ab", "ac"]).statistics();
+
+        assert_eq!(2, statistics.group_size());
+        assert_eq!(3, statistics.anyone_count());
+        assert_eq!(1, statistics.everyone_count());
+        assert_eq!(vec!['a'], statistics.most_common());
+        assert_eq!(vec!['b', 'c'], statistics.least_common());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn example_survey_statistics() {
+        let forms = vec![
+            form(&["abc"]),
+            form(&["a", "b", "c"]),
+            form(&["ab", "ac"]),
+            form(&["a", "a", "a", "a"]),
+            form(&["b"])
+        ];
+
+        let statistics = SurveyStatistics::new(&forms);
+
+        assert_eq!(11, statistics.anyone_sum());
+        assert_eq!(6, statistics.everyone_sum());
+        assert_eq!(11, statistics.total().group_size());
+        assert_eq!(Some(&8), statistics.total().question_counts().get(&'a'));
+        assert_eq!(vec!['a'], statistics.total().most_common());
+        assert_eq!(Some(&4), statistics.groups_by_question().get(&'a'));
+        assert_eq!(Some(&2), statistics.group_size_histogram().get(&1));
+    }
+}
@@ -3,31 +3,52 @@ use std::fs::{File};
 use std::io::{self, BufRead};
 use std::path::Path;
 
-mod form;
-use form::{Form, FormEntry};
-
-pub fn run(config: Config) -> Result<usize, Box<dyn Error>> {
-    let filename = config.filename;
+use config::Config;
+use form::{Form, FormEntry, SurveyStatistics};
+use report::render_report;
+
+pub mod config;
+pub mod form;
+pub mod report;
+
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
+    let forms = read_forms(&config.filename)?;
+
+    match config.report {
+        Some(format) => {
+            let statistics = SurveyStatistics::new(&forms);
+            Ok(render_report(&statistics, format))
+        },
+        None => {
+            let sum: usize = forms.iter().map(|form| form.count()).sum();
+            Ok(sum.to_string())
+        }
+    }
+}
 
-    let mut sum = 0;
+pub fn read_forms<P>(filename: P) -> Result<Vec<Form>, Box<dyn Error>>
+where P: AsRef<Path>, {
+    let mut forms = Vec::new();
     let mut form = Form::new();
 
     let lines = read_lines(filename)?;
     for line in lines {
         let line = line?;
-        if line.len() > 0 {
+        if !line.is_empty() {
             let form_entry = line.parse::<FormEntry>()?;
             form.insert(form_entry);
-        } else {
-            sum += form.count();
-            form.clear();
+        } else if !form.is_empty() {
+            forms.push(form);
+            form = Form::new();
         }
     }
 
     // Add final form, if any
-    sum += form.count();
+    if !form.is_empty() {
+        forms.push(form);
+    }
 
-    Ok(sum)
+    Ok(forms)
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -35,20 +56,3 @@ where P: AsRef<Path>, {
     let file = File::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
-
-pub struct Config {
-    pub filename: String
-}
-
-impl Config {
-    pub fn new(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 2 {
-            return Err("not enough arguments");
-        }
-
-        let filename = args[1].clone();
-    
-        Ok(Config { filename })
-    }
-}
-
@@ -1,7 +1,7 @@
 use std::env;
 use std::process;
 
-use customs::{run, Config};
+use customs::{config::Config, run};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
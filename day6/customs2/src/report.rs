@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+use crate::form::{FormStatistics, SurveyStatistics};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Table,
+    Csv
+}
+
+impl FromStr for ReportFormat {
+    type Err = ParseReportFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(ReportFormat::Table),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(ParseReportFormatError::UnknownFormat(s.to_string()))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseReportFormatError {
+    UnknownFormat(String)
+}
+
+impl Display for ParseReportFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            ParseReportFormatError::UnknownFormat(format) => format!("unknown report format '{}', expected table or csv", format)
+        })
+    }
+}
+
+impl Error for ParseReportFormatError { }
+
+pub fn render_report(statistics: &SurveyStatistics, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Table => render_table(statistics),
+        ReportFormat::Csv => render_csv(statistics)
+    }
+}
+
+fn render_table(statistics: &SurveyStatistics) -> String {
+    let mut output = String::new();
+    let total = statistics.total();
+
+    writeln!(output, "groups: {}", statistics.groups().len()).unwrap();
+    writeln!(output, "people: {}", total.group_size()).unwrap();
+    writeln!(output, "anyone sum: {}", statistics.anyone_sum()).unwrap();
+    writeln!(output, "everyone sum: {}", statistics.everyone_sum()).unwrap();
+    writeln!(output, "most common: {}", join_answers(&total.most_common())).unwrap();
+    writeln!(output, "least common: {}", join_answers(&total.least_common())).unwrap();
+    writeln!(output).unwrap();
+
+    writeln!(output, "{:<10}{:>8}{:>8}", "question", "people", "groups").unwrap();
+    for (answer, count) in total.question_counts() {
+        let groups = statistics.groups_by_question().get(answer).unwrap_or(&0);
+        writeln!(output, "{:<10}{:>8}{:>8}", answer, count, groups).unwrap();
+    }
+    writeln!(output).unwrap();
+
+    writeln!(output, "{:<10}{:>8}  histogram", "size", "groups").unwrap();
+    for (size, count) in statistics.group_size_histogram() {
+        writeln!(output, "{:<10}{:>8}  {}", size, count, "#".repeat(*count)).unwrap();
+    }
+    writeln!(output).unwrap();
+
+    writeln!(output, "{:<8}{:>6}{:>8}{:>10}  {:<14}least common", "group", "size", "anyone", "everyone", "most common").unwrap();
+    for (index, group) in statistics.groups().iter().enumerate() {
+        writeln!(output, "{:<8}{:>6}{:>8}{:>10}  {:<14}{}",
+            index + 1,
+            group.group_size(),
+            group.anyone_count(),
+            group.everyone_count(),
+            join_answers(&group.most_common()),
+            join_answers(&group.least_common())).unwrap();
+    }
+
+    output
+}
+
+fn render_csv(statistics: &SurveyStatistics) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "group,size,question,count").unwrap();
+    for (index, group) in statistics.groups().iter().enumerate() {
+        write_csv_rows(&mut output, &(index + 1).to_string(), group);
+    }
+    write_csv_rows(&mut output, "all", statistics.total());
+
+    output
+}
+
+fn write_csv_rows(output: &mut String, label: &str, statistics: &FormStatistics) {
+    for (answer, count) in statistics.question_counts() {
+        writeln!(output, "{},{},{},{}", label, statistics.group_size(), answer, count).unwrap();
+    }
+}
+
+fn join_answers(answers: &[char]) -> String {
+    answers.iter().collect()
+}
@@ -0,0 +1,56 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::query::{ParseQueryError, Query};
+
+pub struct Config {
+    pub filename: String,
+    pub command: Command
+}
+
+pub enum Command {
+    CountRequired,
+    Query(Query),
+    Dot(Option<String>)
+}
+
+impl Config {
+    pub fn new(args: &[String]) -> Result<Config, ParseConfigError> {
+        if args.len() < 2 {
+            return Err(ParseConfigError::NotEnoughArguments);
+        }
+
+        match args[1].as_str() {
+            "query" => {
+                let filename = args.get(2).cloned().ok_or(ParseConfigError::NotEnoughArguments)?;
+                let query = Query::from_args(&args[3..]).map_err(ParseConfigError::InvalidQuery)?;
+
+                Ok(Config { filename, command: Command::Query(query) })
+            },
+            "dot" => {
+                let filename = args.get(2).cloned().ok_or(ParseConfigError::NotEnoughArguments)?;
+                let bag = args.get(3).cloned();
+
+                Ok(Config { filename, command: Command::Dot(bag) })
+            },
+            filename => Ok(Config { filename: String::from(filename), command: Command::CountRequired })
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseConfigError {
+    NotEnoughArguments,
+    InvalidQuery(ParseQueryError)
+}
+
+impl Display for ParseConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseConfigError::NotEnoughArguments => writeln!(f, "not enough arguments"),
+            ParseConfigError::InvalidQuery(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl Error for ParseConfigError { }
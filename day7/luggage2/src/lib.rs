@@ -3,13 +3,30 @@ use std::fs::{File};
 use std::io::{self, BufRead};
 use std::path::Path;
 
+use config::{Command, Config};
+use query::QueryError;
 use rules::{Rule, RuleSet};
 
-mod rules;
+pub mod config;
+pub mod query;
+pub mod rules;
 
-pub fn run(config: Config) -> Result<usize, Box<dyn Error>> {
-    let filename = config.filename;
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
+    let ruleset = read_ruleset(config.filename)?;
 
+    match config.command {
+        Command::CountRequired => Ok(ruleset.count_required("shiny gold")?.to_string()),
+        Command::Query(query) => Ok(query.execute(&ruleset)?),
+        Command::Dot(None) => Ok(ruleset.to_dot()),
+        Command::Dot(Some(bag)) => match ruleset.subgraph_to_dot(&bag) {
+            Some(dot) => Ok(dot),
+            None => Err(Box::new(QueryError::UnknownBag(bag)))
+        }
+    }
+}
+
+pub fn read_ruleset<P>(filename: P) -> Result<RuleSet, Box<dyn Error>>
+where P: AsRef<Path>, {
     let mut ruleset = RuleSet::new();
 
     let lines = read_lines(filename)?;
@@ -17,9 +34,9 @@ pub fn run(config: Config) -> Result<usize, Box<dyn Error>> {
         ruleset.add_rule(line?.parse::<Rule>()?);
     }
 
-    let count = ruleset.count_required("shiny gold");
+    ruleset.validate()?;
 
-    Ok(count)
+    Ok(ruleset)
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -27,20 +44,3 @@ where P: AsRef<Path>, {
     let file = File::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
-
-pub struct Config {
-    pub filename: String
-}
-
-impl Config {
-    pub fn new(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 2 {
-            return Err("not enough arguments");
-        }
-
-        let filename = args[1].clone();
-    
-        Ok(Config { filename })
-    }
-}
-
@@ -0,0 +1,184 @@
+use std::error::Error;
+use std::fmt::{Display, Write};
+
+use crate::rules::RuleSet;
+
+#[derive(Debug, PartialEq)]
+pub enum Query {
+    Contents(String),
+    Containers(String),
+    Path(String, String),
+    Empty,
+    Depth(String)
+}
+
+impl Query {
+    pub fn from_args(args: &[String]) -> Result<Query, ParseQueryError> {
+        let name = match args.first() {
+            Some(name) => name.as_str(),
+            None => return Err(ParseQueryError::MissingQuery)
+        };
+
+        let bag = |index: usize| args.get(index)
+            .cloned()
+            .ok_or(ParseQueryError::MissingBag);
+
+        match name {
+            "contents" => Ok(Query::Contents(bag(1)?)),
+            "containers" => Ok(Query::Containers(bag(1)?)),
+            "path" => Ok(Query::Path(bag(1)?, bag(2)?)),
+            "empty" => Ok(Query::Empty),
+            "depth" => Ok(Query::Depth(bag(1)?)),
+            _ => Err(ParseQueryError::UnknownQuery(String::from(name)))
+        }
+    }
+
+    pub fn execute(&self, ruleset: &RuleSet) -> Result<String, QueryError> {
+        for bag in self.bags() {
+            if !ruleset.contains_bag(bag) {
+                return Err(QueryError::UnknownBag(String::from(bag)));
+            }
+        }
+
+        let mut output = String::new();
+
+        match self {
+            Query::Contents(bag) => {
+                let direct = ruleset.direct_contents(bag);
+                for content in ruleset.transitive_contents(bag) {
+                    match direct.iter().find(|(_, name)| *name == content) {
+                        Some((count, _)) => writeln!(output, "{} (directly, {})", content, count).unwrap(),
+                        None => writeln!(output, "{}", content).unwrap()
+                    }
+                }
+            },
+            Query::Containers(bag) => {
+                for container in ruleset.transitive_containers(bag) {
+                    writeln!(output, "{}", container).unwrap();
+                }
+            },
+            Query::Path(from, to) => {
+                match ruleset.shortest_path(from, to) {
+                    Some(path) => writeln!(output, "{}", path.join(" -> ")).unwrap(),
+                    None => return Err(QueryError::NoPath(from.clone(), to.clone()))
+                }
+            },
+            Query::Empty => {
+                for bag in ruleset.empty_bags() {
+                    writeln!(output, "{}", bag).unwrap();
+                }
+            },
+            Query::Depth(bag) => {
+                writeln!(output, "{}", ruleset.depth(bag).unwrap_or(0)).unwrap();
+            }
+        }
+
+        Ok(output.trim_end().to_string())
+    }
+
+    fn bags(&self) -> Vec<&str> {
+        match self {
+            Query::Contents(bag) | Query::Containers(bag) | Query::Depth(bag) => vec![bag],
+            Query::Path(from, to) => vec![from, to],
+            Query::Empty => Vec::new()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseQueryError {
+    MissingQuery,
+    MissingBag,
+    UnknownQuery(String)
+}
+
+impl Display for ParseQueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            ParseQueryError::MissingQuery => String::from("missing query (contents, containers, path, empty or depth)"),
+            ParseQueryError::MissingBag => String::from("missing bag for query"),
+            ParseQueryError::UnknownQuery(name) => format!("unknown query: {}", name)
+        })
+    }
+}
+
+impl Error for ParseQueryError { }
+
+#[derive(Debug)]
+pub enum QueryError {
+    UnknownBag(String),
+    NoPath(String, String)
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            QueryError::UnknownBag(bag) => format!("unknown bag: {}", bag),
+            QueryError::NoPath(from, to) => format!("{} can't contain {}", from, to)
+        })
+    }
+}
+
+impl Error for QueryError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rule;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| String::from(*arg)).collect()
+    }
+
+    fn example_ruleset() -> RuleSet {
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule("light red bags contain 1 bright white bag, 2 muted yellow bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("bright white bags contain 1 shiny gold bag.".parse::<Rule>().unwrap());
+        ruleset.add_rule("muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("shiny gold bags contain 1 dark olive bag.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark olive bags contain 3 faded blue bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("faded blue bags contain no other bags.".parse::<Rule>().unwrap());
+
+        ruleset
+    }
+
+    fn execute(query: &[&str]) -> Result<String, QueryError> {
+        Query::from_args(&args(query)).unwrap().execute(&example_ruleset())
+    }
+
+    #[test]
+    fn parse_queries() {
+        assert_eq!(Query::Contents(String::from("shiny gold")), Query::from_args(&args(&["contents", "shiny gold"])).unwrap());
+        assert_eq!(Query::Containers(String::from("shiny gold")), Query::from_args(&args(&["containers", "shiny gold"])).unwrap());
+        assert_eq!(Query::Path(String::from("light red"), String::from("faded blue")), Query::from_args(&args(&["path", "light red", "faded blue"])).unwrap());
+        assert_eq!(Query::Empty, Query::from_args(&args(&["empty"])).unwrap());
+        assert_eq!(Query::Depth(String::from("light red")), Query::from_args(&args(&["depth", "light red"])).unwrap());
+    }
+
+    #[test]
+    fn parse_invalid_queries() {
+        assert!(matches!(Query::from_args(&[]), Err(ParseQueryError::MissingQuery)));
+        assert!(matches!(Query::from_args(&args(&["contents"])), Err(ParseQueryError::MissingBag)));
+        assert!(matches!(Query::from_args(&args(&["path", "light red"])), Err(ParseQueryError::MissingBag)));
+        assert!(matches!(Query::from_args(&args(&["colours"])), Err(ParseQueryError::UnknownQuery(name)) if name == "colours"));
+    }
+
+    #[test]
+    fn execute_contents_and_containers() {
+        assert_eq!("dark olive (directly, 1)\nfaded blue", execute(&["contents", "shiny gold"]).unwrap());
+        assert_eq!("bright white\nlight red\nmuted yellow", execute(&["containers", "shiny gold"]).unwrap());
+    }
+
+    #[test]
+    fn execute_path_empty_and_depth() {
+        assert_eq!("light red -> muted yellow -> faded blue", execute(&["path", "light red", "faded blue"]).unwrap());
+        assert_eq!("faded blue", execute(&["empty"]).unwrap());
+        assert_eq!("4", execute(&["depth", "light red"]).unwrap());
+    }
+
+    #[test]
+    fn execute_errors() {
+        assert!(matches!(execute(&["depth", "plaid"]), Err(QueryError::UnknownBag(bag)) if bag == "plaid"));
+        assert!(matches!(execute(&["path", "faded blue", "light red"]), Err(QueryError::NoPath(..))));
+    }
+}
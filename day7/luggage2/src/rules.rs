@@ -1,80 +1,403 @@
-use std::{error::Error, fmt::Display, str::FromStr};
+use std::{collections::{HashMap, HashSet, VecDeque}, error::Error, fmt::{Display, Write}, str::FromStr};
 
 use once_cell::sync::Lazy;
+use petgraph::Direction;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::{DfsPostOrder, EdgeRef};
 use regex::Regex;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 pub struct RuleSet {
-    nodes: Vec<RuleSetNode>,
-    edges: Vec<RuleSetEdge>
+    graph: DiGraph<String, usize>,
+    indices: HashMap<String, NodeIndex>,
+    definitions: HashMap<NodeIndex, Vec<(usize, String)>>,
+    redefinitions: Vec<RuleSetIssue>
 }
 
 impl RuleSet {
     pub fn new() -> RuleSet {
-        RuleSet { nodes: Vec::new(), edges: Vec::new() }
+        RuleSet {
+            graph: DiGraph::new(),
+            indices: HashMap::new(),
+            definitions: HashMap::new(),
+            redefinitions: Vec::new()
+        }
     }
 
     pub fn add_rule(&mut self, rule: Rule) {
-        let identifier = rule.identifier;
+        let from = self.node(&rule.identifier);
+
+        let mut other_bags = match rule.contains {
+            Contains::NoOtherBags => Vec::new(),
+            Contains::Some(other_bags) => other_bags
+        };
+        other_bags.sort();
+
+        // The first definition of a color wins; later ones are only recorded
+        // so that validation can report them.
+        if let Some(existing) = self.definitions.get(&from) {
+            self.redefinitions.push(if *existing == other_bags {
+                RuleSetIssue::DuplicateRule(rule.identifier)
+            } else {
+                RuleSetIssue::ConflictingRule(rule.identifier)
+            });
+
+            return;
+        }
 
-        if let Contains::Some(other_bags) = rule.contains {
-            for (count, to) in other_bags {
-                self.edges.push(RuleSetEdge { from: identifier.clone(), to, count });
+        for (count, to) in other_bags.iter() {
+            let to = self.node(to);
+            self.graph.add_edge(from, to, *count);
+        }
+
+        self.definitions.insert(from, other_bags);
+    }
+
+    pub fn validate(&self) -> Result<(), ValidateRuleSetError> {
+        let mut issues = self.redefinitions.clone();
+
+        for edge in self.graph.edge_references() {
+            if !self.definitions.contains_key(&edge.target()) {
+                issues.push(RuleSetIssue::UndefinedColor {
+                    container: self.graph[edge.source()].clone(),
+                    color: self.graph[edge.target()].clone()
+                });
             }
         }
-        
-        self.nodes.push(RuleSetNode { identifier });
+
+        for cycle in self.cycles() {
+            issues.push(RuleSetIssue::Cycle(cycle));
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidateRuleSetError { issues })
+        }
     }
 
     pub fn count_combinations(&self, target: &str) -> usize {
-        let mut count = 0;
+        match self.indices.get(target) {
+            Some(target) => self.containers(*target).len(),
+            None => 0
+        }
+    }
+
+    pub fn count_required(&self, target: &str) -> Result<usize, CountRequiredError> {
+        match self.indices.get(target) {
+            Some(target) => Ok(self.totals(*target)?[target]),
+            None => Ok(0)
+        }
+    }
+
+    pub fn contains_bag(&self, bag: &str) -> bool {
+        self.indices.contains_key(bag)
+    }
 
-        for node in &self.nodes {
-            if self.is_valid(&node.identifier, target) {
-                count = count + 1;
+    pub fn direct_contents(&self, bag: &str) -> Vec<(usize, String)> {
+        match self.indices.get(bag) {
+            Some(bag) => self.definitions.get(bag).cloned().unwrap_or_default(),
+            None => Vec::new()
+        }
+    }
+
+    pub fn transitive_contents(&self, bag: &str) -> Vec<String> {
+        let bag = match self.indices.get(bag) {
+            Some(bag) => *bag,
+            None => return Vec::new()
+        };
+
+        let mut contents = HashSet::new();
+        let mut stack: Vec<NodeIndex> = self.graph.neighbors(bag).collect();
+
+        while let Some(node) = stack.pop() {
+            if contents.insert(node) {
+                stack.extend(self.graph.neighbors(node));
             }
         }
 
-        count
+        self.sorted_names(contents)
+    }
+
+    pub fn transitive_containers(&self, bag: &str) -> Vec<String> {
+        match self.indices.get(bag) {
+            Some(bag) => self.sorted_names(self.containers(*bag)),
+            None => Vec::new()
+        }
     }
 
-    fn is_valid(&self, identifier: &str, target: &str) -> bool {
-        for edge in &self.edges {
-            if edge.from == identifier {
-                if edge.to == target {
-                    return true;
-                } else if self.is_valid(&edge.to, target) {
-                    return true;
+    pub fn shortest_path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let from = *self.indices.get(from)?;
+        let to = *self.indices.get(to)?;
+
+        let mut previous: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+
+        while let Some(node) = queue.pop_front() {
+            if node == to {
+                break;
+            }
+
+            for next in self.graph.neighbors(node) {
+                if next != from && !previous.contains_key(&next) {
+                    previous.insert(next, node);
+                    queue.push_back(next);
                 }
             }
         }
 
-        false
+        if from != to && !previous.contains_key(&to) {
+            return None;
+        }
+
+        let mut path = vec![self.graph[to].clone()];
+        let mut node = to;
+        while node != from {
+            node = previous[&node];
+            path.push(self.graph[node].clone());
+        }
+        path.reverse();
+
+        Some(path)
     }
-    
-    pub fn count_required(&self, target: &str) -> usize {
-        let mut count = 0;
 
-        for edge in &self.edges {
-            if edge.from == target {
-                count = count + edge.count * (self.count_required(&edge.to) + 1);
+    pub fn empty_bags(&self) -> Vec<String> {
+        let empty = self.definitions.iter()
+            .filter(|(_, contents)| contents.is_empty())
+            .map(|(node, _)| *node);
+
+        self.sorted_names(empty)
+    }
+
+    pub fn depth(&self, bag: &str) -> Option<usize> {
+        let bag = *self.indices.get(bag)?;
+
+        let mut depths = HashMap::new();
+        let mut dfs = DfsPostOrder::new(&self.graph, bag);
+
+        while let Some(node) = dfs.next(&self.graph) {
+            let depth = self.graph.neighbors(node)
+                .map(|next| depths.get(&next).copied().unwrap_or(0) + 1)
+                .max()
+                .unwrap_or(0);
+
+            depths.insert(node, depth);
+        }
+
+        depths.get(&bag).copied()
+    }
+
+    pub fn to_dot(&self) -> String {
+        self.write_dot(&self.graph.node_indices().collect())
+    }
+
+    pub fn subgraph_to_dot(&self, bag: &str) -> Option<String> {
+        let bag = *self.indices.get(bag)?;
+
+        let mut nodes: HashSet<NodeIndex> = self.containers(bag);
+        let mut dfs = DfsPostOrder::new(&self.graph, bag);
+        while let Some(node) = dfs.next(&self.graph) {
+            nodes.insert(node);
+        }
+
+        Some(self.write_dot(&nodes))
+    }
+
+    fn write_dot(&self, nodes: &HashSet<NodeIndex>) -> String {
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph luggage {{").unwrap();
+        for node in self.graph.node_indices().filter(|node| nodes.contains(node)) {
+            writeln!(dot, "    \"{}\";", self.graph[node]).unwrap();
+        }
+        for edge in self.graph.edge_references() {
+            if nodes.contains(&edge.source()) && nodes.contains(&edge.target()) {
+                writeln!(dot, "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                    self.graph[edge.source()],
+                    self.graph[edge.target()],
+                    edge.weight()).unwrap();
+            }
+        }
+        writeln!(dot, "}}").unwrap();
+
+        dot
+    }
+
+    fn sorted_names<I>(&self, nodes: I) -> Vec<String>
+    where I: IntoIterator<Item = NodeIndex>, {
+        let mut names: Vec<String> = nodes.into_iter()
+            .map(|node| self.graph[node].clone())
+            .collect();
+        names.sort();
+
+        names
+    }
+
+    fn node(&mut self, identifier: &str) -> NodeIndex {
+        if let Some(index) = self.indices.get(identifier) {
+            return *index;
+        }
+
+        let index = self.graph.add_node(String::from(identifier));
+        self.indices.insert(String::from(identifier), index);
+
+        index
+    }
+
+    // Every bag that can eventually contain the target, found by walking the
+    // "is contained by" edges backwards from the target once.
+    fn containers(&self, target: NodeIndex) -> HashSet<NodeIndex> {
+        let mut containers = HashSet::new();
+        let mut stack: Vec<NodeIndex> = self.graph.neighbors_directed(target, Direction::Incoming).collect();
+
+        while let Some(node) = stack.pop() {
+            if containers.insert(node) {
+                stack.extend(self.graph.neighbors_directed(node, Direction::Incoming));
+            }
+        }
+
+        containers
+    }
+
+    // Every cycle closed by a back edge of a depth-first walk, as the full
+    // path of colors from the repeated bag back to itself.
+    fn cycles(&self) -> Vec<Vec<String>> {
+        let mut cycles = Vec::new();
+        let mut visits = vec![Visit::New; self.graph.node_count()];
+
+        for start in self.graph.node_indices() {
+            if visits[start.index()] != Visit::New {
+                continue;
+            }
+
+            visits[start.index()] = Visit::Active;
+            let mut stack = vec![(start, self.graph.neighbors(start))];
+
+            while let Some((node, neighbors)) = stack.last_mut() {
+                let node = *node;
+
+                match neighbors.next() {
+                    Some(next) => match visits[next.index()] {
+                        Visit::New => {
+                            visits[next.index()] = Visit::Active;
+                            stack.push((next, self.graph.neighbors(next)));
+                        },
+                        Visit::Active => {
+                            let position = stack.iter().position(|(n, _)| *n == next).unwrap();
+                            let mut cycle: Vec<String> = stack[position..].iter()
+                                .map(|(n, _)| self.graph[*n].clone())
+                                .collect();
+                            cycle.push(self.graph[next].clone());
+
+                            cycles.push(cycle);
+                        },
+                        Visit::Done => { }
+                    },
+                    None => {
+                        visits[node.index()] = Visit::Done;
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        cycles
+    }
+
+    // Total number of bags inside every bag reachable from the target. A
+    // post-order walk guarantees each bag's contents are totalled before any
+    // bag that holds it, so every bag is computed exactly once.
+    fn totals(&self, target: NodeIndex) -> Result<HashMap<NodeIndex, usize>, CountRequiredError> {
+        let mut totals = HashMap::new();
+        let mut dfs = DfsPostOrder::new(&self.graph, target);
+
+        while let Some(node) = dfs.next(&self.graph) {
+            let mut total: usize = 0;
+            for edge in self.graph.edges_directed(node, Direction::Outgoing) {
+                let inner: usize = totals.get(&edge.target()).copied().unwrap_or(0);
+                total = inner.checked_add(1)
+                    .and_then(|bags| edge.weight().checked_mul(bags))
+                    .and_then(|bags| total.checked_add(bags))
+                    .ok_or(CountRequiredError::Overflow)?;
             }
+
+            totals.insert(node, total);
         }
 
-        count
+        Ok(totals)
     }
 }
 
 #[derive(Debug, PartialEq)]
-struct RuleSetNode {
-    identifier: String
+pub enum CountRequiredError {
+    Overflow
 }
 
-#[derive(Debug, PartialEq)]
-struct RuleSetEdge {
-    from: String,
-    to: String,
-    count: usize
+impl Display for CountRequiredError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            CountRequiredError::Overflow => "required bag count doesn't fit in a usize"
+        })
+    }
+}
+
+impl Error for CountRequiredError { }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Visit {
+    New,
+    Active,
+    Done
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleSetIssue {
+    Cycle(Vec<String>),
+    DuplicateRule(String),
+    ConflictingRule(String),
+    UndefinedColor { container: String, color: String }
+}
+
+impl Display for RuleSetIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleSetIssue::Cycle(path) => write!(f, "cycle: {}", path.join(" -> ")),
+            RuleSetIssue::DuplicateRule(identifier) => write!(f, "duplicate rule for {}", identifier),
+            RuleSetIssue::ConflictingRule(identifier) => write!(f, "conflicting rules for {}", identifier),
+            RuleSetIssue::UndefinedColor { container, color } => write!(f, "{} contains undefined color {}", container, color)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ValidateRuleSetError {
+    issues: Vec<RuleSetIssue>
+}
+
+impl ValidateRuleSetError {
+    pub fn issues(&self) -> &[RuleSetIssue] {
+        &self.issues
+    }
+}
+
+impl Display for ValidateRuleSetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "invalid rule set")?;
+        for issue in self.issues.iter() {
+            writeln!(f, "  {}", issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for ValidateRuleSetError { }
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::new()
+    }
 }
 
 // light red bags contain 1 bright white bag, 2 muted yellow bags.
@@ -106,9 +429,8 @@ impl FromStr for Rule {
             let identifier = String::from(&rule_captures[1]);
             let contains_text = &rule_captures[2];
 
-            let contains: Contains;
-            if contains_text == "no other bags" {
-                contains = Contains::NoOtherBags;
+            let contains = if contains_text == "no other bags" {
+                Contains::NoOtherBags
             } else {
                 let mut other_bags: Vec<(usize, String)> = Vec::new();
                 for contains_clause in contains_text.split(',') {
@@ -126,12 +448,12 @@ impl FromStr for Rule {
                     }
                 }
 
-                contains = Contains::Some(other_bags);
-            }
+                Contains::Some(other_bags)
+            };
 
-            return Ok(Rule { identifier, contains });
+            Ok(Rule { identifier, contains })
         } else {
-            return Err(ParseRuleError { });
+            Err(ParseRuleError { })
         }
     }
 }
@@ -208,6 +530,165 @@ mod tests {
         
         let count = ruleset.count_required("shiny gold");
 
-        assert_eq!(126, count);
+        assert_eq!(Ok(126), count);
+    }
+
+    #[test]
+    fn deep_ladder_ruleset() {
+        // Every layer holds both bags of the next layer, so a naive walk
+        // visits 2^40 paths; the indexed queries should be instant.
+        let mut ruleset = RuleSet::new();
+        for layer in 0..40 {
+            for side in &["left", "right"] {
+                ruleset.add_rule(Rule {
+                    identifier: format!("{} {}", side, layer),
+                    contains: Contains::Some(vec![
+                        (1, format!("left {}", layer + 1)),
+                        (1, format!("right {}", layer + 1))
+                    ])
+                });
+            }
+        }
+
+        assert_eq!(78, ruleset.count_combinations("left 39"));
+        assert_eq!(Ok(2usize.pow(41) - 2), ruleset.count_required("left 0"));
+    }
+
+    #[test]
+    fn overflowing_ruleset() {
+        // 1000 of each bag in the next layer, 10 layers deep: 1000^10 bags.
+        let mut ruleset = RuleSet::new();
+        for layer in 0..10 {
+            ruleset.add_rule(Rule {
+                identifier: format!("layer {}", layer),
+                contains: Contains::Some(vec![(1000, format!("layer {}", layer + 1))])
+            });
+        }
+
+        assert_eq!(Err(CountRequiredError::Overflow), ruleset.count_required("layer 0"));
+        assert_eq!(Ok(1001000), ruleset.count_required("layer 8"));
+    }
+
+    #[test]
+    fn long_chain_ruleset() {
+        let mut ruleset = RuleSet::new();
+        for n in 0..50_000 {
+            ruleset.add_rule(Rule {
+                identifier: format!("color {}", n),
+                contains: Contains::Some(vec![(1, format!("color {}", n + 1))])
+            });
+        }
+
+        assert_eq!(50_000, ruleset.count_combinations("color 50000"));
+        assert_eq!(Ok(50_000), ruleset.count_required("color 0"));
+    }
+
+    #[test]
+    fn validate_example_ruleset() {
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule("shiny gold bags contain 2 dark red bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark red bags contain no other bags.".parse::<Rule>().unwrap());
+
+        assert!(ruleset.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_cycle() {
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule("shiny gold bags contain 1 dark red bag.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark red bags contain 2 dark orange bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark orange bags contain 1 dark red bag.".parse::<Rule>().unwrap());
+
+        let error = ruleset.validate().unwrap_err();
+
+        assert_eq!(&[RuleSetIssue::Cycle(vec![
+            String::from("dark red"),
+            String::from("dark orange"),
+            String::from("dark red")
+        ])], error.issues());
+
+        assert_eq!(3, ruleset.count_combinations("dark red"));
+    }
+
+    #[test]
+    fn validate_duplicate_and_conflicting_rules() {
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule("shiny gold bags contain 1 dark red bag, 2 dark blue bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("shiny gold bags contain 2 dark blue bags, 1 dark red bag.".parse::<Rule>().unwrap());
+        ruleset.add_rule("shiny gold bags contain 3 dark red bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark red bags contain no other bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark blue bags contain no other bags.".parse::<Rule>().unwrap());
+
+        let error = ruleset.validate().unwrap_err();
+
+        assert_eq!(&[
+            RuleSetIssue::DuplicateRule(String::from("shiny gold")),
+            RuleSetIssue::ConflictingRule(String::from("shiny gold"))
+        ], error.issues());
+
+        assert_eq!(Ok(3), ruleset.count_required("shiny gold"));
+    }
+
+    #[test]
+    fn validate_undefined_color() {
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule("shiny gold bags contain 1 dark red bag.".parse::<Rule>().unwrap());
+
+        let error = ruleset.validate().unwrap_err();
+
+        assert_eq!(&[RuleSetIssue::UndefinedColor {
+            container: String::from("shiny gold"),
+            color: String::from("dark red")
+        }], error.issues());
+    }
+
+    fn example_ruleset_for_queries() -> RuleSet {
+        let mut ruleset = RuleSet::new();
+        ruleset.add_rule("light red bags contain 1 bright white bag, 2 muted yellow bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark orange bags contain 3 bright white bags, 4 muted yellow bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("bright white bags contain 1 shiny gold bag.".parse::<Rule>().unwrap());
+        ruleset.add_rule("muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("shiny gold bags contain 1 dark olive bag, 2 vibrant plum bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dark olive bags contain 3 faded blue bags, 4 dotted black bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("faded blue bags contain no other bags.".parse::<Rule>().unwrap());
+        ruleset.add_rule("dotted black bags contain no other bags.".parse::<Rule>().unwrap());
+
+        ruleset
+    }
+
+    #[test]
+    fn query_contents_and_containers() {
+        let ruleset = example_ruleset_for_queries();
+
+        assert_eq!(vec!["dark olive", "dotted black", "faded blue", "vibrant plum"], ruleset.transitive_contents("shiny gold"));
+        assert_eq!(vec!["bright white", "dark orange", "light red", "muted yellow"], ruleset.transitive_containers("shiny gold"));
+        assert_eq!(vec!["dotted black", "faded blue"], ruleset.empty_bags());
+    }
+
+    #[test]
+    fn query_shortest_path_and_depth() {
+        let ruleset = example_ruleset_for_queries();
+
+        assert_eq!(Some(vec![
+            String::from("light red"),
+            String::from("muted yellow"),
+            String::from("faded blue")
+        ]), ruleset.shortest_path("light red", "faded blue"));
+        assert_eq!(None, ruleset.shortest_path("faded blue", "light red"));
+
+        assert_eq!(Some(4), ruleset.depth("light red"));
+        assert_eq!(Some(0), ruleset.depth("faded blue"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn subgraph_dot() {
+        let ruleset = example_ruleset_for_queries();
+
+        let dot = ruleset.subgraph_to_dot("dark olive").unwrap();
+
+        assert!(dot.contains("\"shiny gold\" -> \"dark olive\" [label=\"1\"];"));
+        assert!(dot.contains("\"dark olive\" -> \"faded blue\" [label=\"3\"];"));
+        assert!(!dot.contains("\"vibrant plum\""));
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::{Display, Write};
+
+use crate::boot::Instruction;
+
+// Assembles boot code that may use comments (`#` or `;` to end of line),
+// blank lines and labels (`loop:`) as jump targets. Labels are resolved to
+// the relative offsets the machine expects, so plain puzzle input assembles
+// to exactly the same program.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, AssembleError> {
+    let mut labels = HashMap::new();
+    let mut statements = Vec::new();
+
+    for (index, raw) in source.lines().enumerate() {
+        let line = index + 1;
+        let mut text = strip_comment(raw).trim();
+
+        while let Some((label, rest)) = split_label(text) {
+            if labels.insert(String::from(label), statements.len()).is_some() {
+                return Err(AssembleError::DuplicateLabel { line, label: String::from(label) });
+            }
+
+            text = rest.trim();
+        }
+
+        if !text.is_empty() {
+            statements.push((line, text));
+        }
+    }
+
+    let mut program = Vec::new();
+
+    for (index, (line, text)) in statements.into_iter().enumerate() {
+        let mut tokens: Vec<String> = text.split_whitespace().map(String::from).collect();
+
+        if matches!(tokens[0].as_str(), "jmp" | "jz" | "jnz") {
+            let target = tokens.last_mut().unwrap();
+            if target.parse::<isize>().is_err() {
+                let label_index = labels.get(target.as_str())
+                    .ok_or_else(|| AssembleError::UnknownLabel { line, label: target.clone() })?;
+
+                *target = format!("{:+}", *label_index as isize - index as isize);
+            }
+        }
+
+        let instruction = tokens.join(" ").parse::<Instruction>()
+            .map_err(|_| AssembleError::InvalidInstruction { line, text: String::from(text) })?;
+
+        program.push(instruction);
+    }
+
+    Ok(program)
+}
+
+// Prints a program back out in puzzle format, one instruction per line.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let mut output = String::new();
+    for instruction in program {
+        writeln!(output, "{}", instruction).unwrap();
+    }
+
+    output
+}
+
+// Prints a program with every in-range jump target replaced by a label named
+// after the line it points to.
+pub fn disassemble_with_labels(program: &[Instruction]) -> String {
+    let mut labels = BTreeMap::new();
+    for (index, instruction) in program.iter().enumerate() {
+        if let Some(target) = jump_target(index, instruction, program.len()) {
+            labels.insert(target, format!("l{}", target + 1));
+        }
+    }
+
+    let mut output = String::new();
+    for index in 0..=program.len() {
+        if let Some(label) = labels.get(&index) {
+            writeln!(output, "{}:", label).unwrap();
+        }
+
+        if let Some(instruction) = program.get(index) {
+            let label = jump_target(index, instruction, program.len())
+                .and_then(|target| labels.get(&target));
+
+            match (instruction, label) {
+                (Instruction::Jump(_), Some(label)) => writeln!(output, "    jmp {}", label),
+                (Instruction::JumpIfZero(register, _), Some(label)) => writeln!(output, "    jz {} {}", register, label),
+                (Instruction::JumpIfNotZero(register, _), Some(label)) => writeln!(output, "    jnz {} {}", register, label),
+                _ => writeln!(output, "    {}", instruction)
+            }.unwrap();
+        }
+    }
+
+    output
+}
+
+fn jump_target(index: usize, instruction: &Instruction, end: usize) -> Option<usize> {
+    let target = index as isize + instruction.jump_offset()?;
+    if target >= 0 && target as usize <= end {
+        Some(target as usize)
+    } else {
+        None
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(['#', ';']) {
+        Some(position) => &line[..position],
+        None => line
+    }
+}
+
+fn split_label(text: &str) -> Option<(&str, &str)> {
+    let (label, rest) = text.split_once(':')?;
+
+    let mut chars = label.chars();
+    let starts_well = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    if starts_well && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Some((label, rest))
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AssembleError {
+    InvalidInstruction { line: usize, text: String },
+    UnknownLabel { line: usize, label: String },
+    DuplicateLabel { line: usize, label: String }
+}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            AssembleError::InvalidInstruction { line, text } => format!("line {}: couldn't parse instruction: {}", line, text),
+            AssembleError::UnknownLabel { line, label } => format!("line {}: unknown label: {}", line, label),
+            AssembleError::DuplicateLabel { line, label } => format!("line {}: duplicate label: {}", line, label)
+        })
+    }
+}
+
+impl Error for AssembleError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boot::Register;
+
+    #[test]
+    fn assemble_puzzle_format() {
+        let program = assemble("nop +0\nacc +1\njmp -2\n").unwrap();
+
+        assert_eq!(vec![Instruction::Noop(0), Instruction::Acc(1), Instruction::Jump(-2)], program);
+    }
+
+    #[test]
+    fn assemble_labels_and_comments() {
+        let source = "\
+            set b +3      # count down from three
+            loop: acc +2
+            add b -1
+            jnz b loop    ; back to the top
+            jmp done
+
+            acc +100
+            done:
+            hlt
+        ";
+
+        let program = assemble(source).unwrap();
+
+        assert_eq!(vec![
+            Instruction::Set(Register::B, 3),
+            Instruction::Acc(2),
+            Instruction::Add(Register::B, -1),
+            Instruction::JumpIfNotZero(Register::B, -2),
+            Instruction::Jump(2),
+            Instruction::Acc(100),
+            Instruction::Halt
+        ], program);
+    }
+
+    #[test]
+    fn assemble_errors() {
+        assert_eq!(Err(AssembleError::UnknownLabel { line: 1, label: String::from("nowhere") }), assemble("jmp nowhere"));
+        assert_eq!(Err(AssembleError::DuplicateLabel { line: 2, label: String::from("a") }), assemble("a: nop +0\na: nop +0"));
+        assert_eq!(Err(AssembleError::InvalidInstruction { line: 1, text: String::from("mul acc +2") }), assemble("mul acc +2"));
+    }
+
+    #[test]
+    fn disassemble_round_trip() {
+        let program = assemble("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6").unwrap();
+
+        let labelled = disassemble_with_labels(&program);
+        assert!(labelled.contains("l4:\n    acc +3"));
+        assert!(labelled.contains("    jmp l7"));
+
+        assert_eq!(program, assemble(&labelled).unwrap());
+        assert_eq!(program, assemble(&disassemble(&program)).unwrap());
+    }
+}
@@ -1,75 +1,224 @@
-use std::{collections::HashSet, error::Error, fmt::Display, str::FromStr};
+use std::{error::Error, fmt::Display, str::FromStr};
 
-pub fn run_program(program: &Vec<Instruction>) -> isize {
-    for mutation_index in 0..program.len() {
-        if let Some(accum) = does_terminate_with_mutation(program, mutation_index) {
-            return accum;
+use crate::cfg::ControlFlowGraph;
+use crate::machine::{Machine, Status};
+
+// Repairs a program that loops forever by flipping exactly one nop or jmp.
+// Every instruction that reaches the end of the program unchanged is found by
+// walking the control flow backwards from the end once; the fix is then the
+// first instruction on the original (looping) path whose flipped form lands in
+// that set. Both passes visit each instruction at most once.
+pub fn repair_program(program: &[Instruction]) -> Result<Repair, RepairError> {
+    if run_to_end(program).is_some() {
+        return Err(RepairError::AlreadyTerminates);
+    }
+
+    if let Some(index) = program.iter().position(|instruction| instruction.is_conditional()) {
+        return Err(RepairError::UnsupportedInstruction(index + 1));
+    }
+
+    let cfg = ControlFlowGraph::new(program);
+    let end = program.len();
+    let registers = [0; Register::COUNT];
+
+    let mut program_counter = 0;
+    let mut executed_lines = vec![false; end];
+
+    while program_counter < end && !executed_lines[program_counter] {
+        executed_lines[program_counter] = true;
+
+        let instruction = &program[program_counter];
+        if instruction.is_flippable() {
+            let flipped = instruction.flip();
+            if let Some(next) = flipped.next(program_counter, end, &registers) {
+                if next == end || (next < end && cfg.can_terminate(next)) {
+                    let mut patched_program = program.to_vec();
+                    patched_program[program_counter] = flipped.clone();
+
+                    let accumulator = run_to_end(&patched_program)
+                        .ok_or(RepairError::NoRepair)?;
+
+                    return Ok(Repair {
+                        line_number: program_counter + 1,
+                        original: instruction.clone(),
+                        patched: flipped,
+                        accumulator
+                    });
+                }
+            }
         }
+
+        program_counter = match instruction.next(program_counter, end, &registers) {
+            Some(next) => next,
+            None => break
+        };
     }
 
-    0
+    Err(RepairError::NoRepair)
 }
 
-fn does_terminate_with_mutation(program: &Vec<Instruction>, mutation: usize) -> Option<isize> {
-    let mut accum = 0;
-    let mut program_counter: usize = 0;
+fn run_to_end(program: &[Instruction]) -> Option<isize> {
+    let mut machine = Machine::new(program.to_vec());
 
-    let mut executed_lines = HashSet::new();
+    match machine.run() {
+        Status::Terminated => Some(machine.accumulator()),
+        _ => None
+    }
+}
 
-    loop {
-        if program_counter == program.len() {
-            println!("finished w/ mutation at {}", mutation);
-            return Some(accum);
-        } if program_counter > program.len() {
-            return None; // Past program end
-        } else if executed_lines.contains(&program_counter) {
-            return None; // Loop detected
-        }
+#[derive(Debug, PartialEq)]
+pub struct Repair {
+    pub line_number: usize,
+    pub original: Instruction,
+    pub patched: Instruction,
+    pub accumulator: isize
+}
+
+impl Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "line {}: {} -> {}", self.line_number, self.original, self.patched)?;
+        write!(f, "accumulator: {}", self.accumulator)
+    }
+}
 
-        executed_lines.insert(program_counter);
+#[derive(Debug, PartialEq)]
+pub enum RepairError {
+    AlreadyTerminates,
+    NoRepair,
+    UnsupportedInstruction(usize)
+}
 
-        let instruction_at_counter = program.get(program_counter as usize).unwrap();
-        let flipped_instruction = instruction_at_counter.flip();
+impl Display for RepairError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            RepairError::AlreadyTerminates => String::from("program already terminates"),
+            RepairError::NoRepair => String::from("no single nop/jmp flip makes the program terminate"),
+            RepairError::UnsupportedInstruction(line_number) => format!("can't repair conditional jump at line {}", line_number)
+        })
+    }
+}
 
-        let instruction = if mutation == program_counter {
-            &flipped_instruction
-        } else {
-            instruction_at_counter
-        };
+impl Error for RepairError { }
 
-        match instruction {
-            Instruction::Noop(_) => {
-                program_counter = program_counter + 1;
-            },
-            Instruction::Acc(value) => { 
-                accum = accum + value; 
-                program_counter = program_counter + 1;
-            },
-            Instruction::Jump(value) => {
-                program_counter = ((program_counter as isize) + value) as usize;
-            }
+pub struct Line {
+    pub line_number: usize,
+    pub instruction: Instruction
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Register {
+    Acc,
+    B,
+    C,
+    D
+}
+
+impl Register {
+    pub const COUNT: usize = 4;
+
+    pub fn index(&self) -> usize {
+        match self {
+            Register::Acc => 0,
+            Register::B => 1,
+            Register::C => 2,
+            Register::D => 3
         }
     }
 }
 
-pub struct Line {
-    line_number: usize,
-    instruction: Instruction
+impl Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Register::Acc => "acc",
+            Register::B => "b",
+            Register::C => "c",
+            Register::D => "d"
+        })
+    }
 }
 
-#[derive(Debug, PartialEq)]
+impl FromStr for Register {
+    type Err = ParseInstructionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "acc" => Ok(Register::Acc),
+            "b" => Ok(Register::B),
+            "c" => Ok(Register::C),
+            "d" => Ok(Register::D),
+            _ => Err(ParseInstructionError { })
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
     Noop(isize),
     Acc(isize),
-    Jump(isize)
+    Jump(isize),
+    Halt,
+    Add(Register, isize),
+    Set(Register, isize),
+    JumpIfZero(Register, isize),
+    JumpIfNotZero(Register, isize)
 }
 
 impl Instruction {
+    fn is_flippable(&self) -> bool {
+        matches!(self, Instruction::Noop(_) | Instruction::Jump(_))
+    }
+
+    pub(crate) fn is_conditional(&self) -> bool {
+        matches!(self, Instruction::JumpIfZero(_, _) | Instruction::JumpIfNotZero(_, _))
+    }
+
+    // The line executed after this one, or None for a jump before the start
+    // of the program. Halting jumps straight to the end of the program.
+    pub(crate) fn next(&self, program_counter: usize, end: usize, registers: &[isize]) -> Option<usize> {
+        let jump = |offset: isize| {
+            (program_counter as isize).checked_add(offset)
+                .filter(|next| *next >= 0)
+                .map(|next| next as usize)
+        };
+
+        match self {
+            Instruction::Jump(offset) => jump(*offset),
+            Instruction::JumpIfZero(register, offset) if registers[register.index()] == 0 => jump(*offset),
+            Instruction::JumpIfNotZero(register, offset) if registers[register.index()] != 0 => jump(*offset),
+            Instruction::Halt => Some(end),
+            _ => Some(program_counter + 1)
+        }
+    }
+
+    // The relative offset of a jump, if this instruction is one.
+    pub fn jump_offset(&self) -> Option<isize> {
+        match self {
+            Instruction::Jump(offset) => Some(*offset),
+            Instruction::JumpIfZero(_, offset) | Instruction::JumpIfNotZero(_, offset) => Some(*offset),
+            _ => None
+        }
+    }
+
     fn flip(&self) -> Instruction {
         match self {
-            Instruction::Noop(value) => { Instruction::Jump(*value) }
-            Instruction::Acc(value) => { Instruction::Acc(*value) }
-            Instruction::Jump(value) => { Instruction::Noop(*value) }
+            Instruction::Noop(value) => Instruction::Jump(*value),
+            Instruction::Jump(value) => Instruction::Noop(*value),
+            other => other.clone()
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Noop(value) => write!(f, "nop {:+}", value),
+            Instruction::Acc(value) => write!(f, "acc {:+}", value),
+            Instruction::Jump(value) => write!(f, "jmp {:+}", value),
+            Instruction::Halt => write!(f, "hlt"),
+            Instruction::Add(register, value) => write!(f, "add {} {:+}", register, value),
+            Instruction::Set(register, value) => write!(f, "set {} {:+}", register, value),
+            Instruction::JumpIfZero(register, offset) => write!(f, "jz {} {:+}", register, offset),
+            Instruction::JumpIfNotZero(register, offset) => write!(f, "jnz {} {:+}", register, offset)
         }
     }
 }
@@ -78,18 +227,21 @@ impl FromStr for Instruction {
     type Err = ParseInstructionError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((instruction_string, amount_string)) = s.split_once(' ') {
-            if let Ok(amount) = amount_string.parse::<isize>() {
-                return match instruction_string {
-                    "nop" => { Ok(Instruction::Noop(amount)) },
-                    "acc" => { Ok(Instruction::Acc(amount)) },
-                    "jmp" => { Ok(Instruction::Jump(amount)) },
-                    _ => { Err(ParseInstructionError { }) }
-                }
-            }
-        }
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+
+        let value = |token: &str| token.parse::<isize>().map_err(|_| ParseInstructionError { });
 
-        Err(ParseInstructionError { })
+        match tokens.as_slice() {
+            ["nop", amount] => Ok(Instruction::Noop(value(amount)?)),
+            ["acc", amount] => Ok(Instruction::Acc(value(amount)?)),
+            ["jmp", offset] => Ok(Instruction::Jump(value(offset)?)),
+            ["hlt"] => Ok(Instruction::Halt),
+            ["add", register, amount] => Ok(Instruction::Add(register.parse()?, value(amount)?)),
+            ["set", register, amount] => Ok(Instruction::Set(register.parse()?, value(amount)?)),
+            ["jz", register, offset] => Ok(Instruction::JumpIfZero(register.parse()?, value(offset)?)),
+            ["jnz", register, offset] => Ok(Instruction::JumpIfNotZero(register.parse()?, value(offset)?)),
+            _ => Err(ParseInstructionError { })
+        }
     }
 }
 
@@ -119,7 +271,7 @@ mod tests {
     fn nop() {
         let instruction = "nop +1".parse::<Instruction>().unwrap();
 
-        assert_eq!(Instruction::Noop, instruction);
+        assert_eq!(Instruction::Noop(1), instruction);
     }
 
     #[test]
@@ -128,4 +280,39 @@ mod tests {
 
         assert_eq!(Instruction::Jump(-1), instruction);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn extended_instructions() {
+        assert_eq!(Instruction::Halt, "hlt".parse::<Instruction>().unwrap());
+        assert_eq!(Instruction::Add(Register::B, -2), "add b -2".parse::<Instruction>().unwrap());
+        assert_eq!(Instruction::JumpIfNotZero(Register::Acc, 3), "jnz acc +3".parse::<Instruction>().unwrap());
+        assert!("add e +1".parse::<Instruction>().is_err());
+        assert!("hlt +1".parse::<Instruction>().is_err());
+
+        assert_eq!("set c +5", Instruction::Set(Register::C, 5).to_string());
+    }
+
+    #[test]
+    fn repair_example_program() {
+        let program: Vec<Instruction> = vec![
+            "nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3",
+            "acc -99", "acc +1", "jmp -4", "acc +6"
+        ].into_iter().map(|line| line.parse::<Instruction>().unwrap()).collect();
+
+        let repair = repair_program(&program).unwrap();
+
+        assert_eq!(Repair {
+            line_number: 8,
+            original: Instruction::Jump(-4),
+            patched: Instruction::Noop(-4),
+            accumulator: 8
+        }, repair);
+    }
+
+    #[test]
+    fn repair_terminating_program() {
+        let program = vec![Instruction::Acc(1), Instruction::Noop(0)];
+
+        assert_eq!(Err(RepairError::AlreadyTerminates), repair_program(&program));
+    }
+}
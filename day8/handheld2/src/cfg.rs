@@ -0,0 +1,422 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Write;
+
+use crate::boot::Instruction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Target {
+    Line(usize),
+    Exit,
+    OutOfBounds
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub end: usize,
+    pub successors: Vec<Target>
+}
+
+pub struct ControlFlowGraph {
+    program: Vec<Instruction>,
+    successors: Vec<Vec<Target>>,
+    blocks: Vec<BasicBlock>,
+    reachable: Vec<bool>,
+    terminating: Vec<bool>,
+    loops: Vec<Vec<usize>>
+}
+
+impl ControlFlowGraph {
+    pub fn new(program: &[Instruction]) -> ControlFlowGraph {
+        let successors: Vec<Vec<Target>> = program.iter()
+            .enumerate()
+            .map(|(index, instruction)| successors(index, instruction, program.len()))
+            .collect();
+
+        let (blocks, block_of) = basic_blocks(program, &successors);
+        let reachable = reachable_lines(&successors);
+        let terminating = terminating_lines(&successors);
+        let loops = loops(&blocks, &block_of);
+
+        ControlFlowGraph {
+            program: program.to_vec(),
+            successors,
+            blocks,
+            reachable,
+            terminating,
+            loops
+        }
+    }
+
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    pub fn successors(&self, index: usize) -> &[Target] {
+        &self.successors[index]
+    }
+
+    pub fn is_reachable(&self, index: usize) -> bool {
+        self.reachable[index]
+    }
+
+    // Whether some path from this instruction reaches the end of the
+    // program. Without conditional jumps there is only one path, so this is
+    // exactly whether execution from here terminates.
+    pub fn can_terminate(&self, index: usize) -> bool {
+        self.terminating[index]
+    }
+
+    // Each loop is a set of blocks (by index into `blocks`) that can all
+    // reach one another.
+    pub fn loops(&self) -> &[Vec<usize>] {
+        &self.loops
+    }
+
+    pub fn is_infinite_loop(&self, l: usize) -> bool {
+        self.loops[l].iter()
+            .flat_map(|block| self.blocks[*block].start..self.blocks[*block].end)
+            .all(|index| !self.terminating[index])
+    }
+
+    pub fn unreachable_lines(&self) -> Vec<usize> {
+        (0..self.program.len())
+            .filter(|index| !self.reachable[*index])
+            .map(|index| index + 1)
+            .collect()
+    }
+
+    pub fn listing(&self) -> String {
+        let mut output = String::new();
+
+        for (b, block) in self.blocks.iter().enumerate() {
+            let targets: Vec<String> = block.successors.iter()
+                .map(|target| match target {
+                    Target::Line(index) => (index + 1).to_string(),
+                    Target::Exit => String::from("exit"),
+                    Target::OutOfBounds => String::from("out of bounds")
+                })
+                .collect();
+
+            if block.end - block.start == 1 {
+                write!(output, "block {} -> {}", block.end, targets.join(", ")).unwrap();
+            } else {
+                write!(output, "block {}-{} -> {}", block.start + 1, block.end, targets.join(", ")).unwrap();
+            }
+
+            if !self.reachable[block.start] {
+                write!(output, "  [unreachable]").unwrap();
+            }
+            if let Some(l) = self.loop_of(b) {
+                if self.is_infinite_loop(l) {
+                    write!(output, "  [infinite loop {}]", l + 1).unwrap();
+                } else {
+                    write!(output, "  [loop {}]", l + 1).unwrap();
+                }
+            }
+            writeln!(output).unwrap();
+
+            for index in block.start..block.end {
+                let note = if self.terminating[index] { "reaches end" } else { "never ends" };
+                writeln!(output, "  {:>5}  {:<16}; {}", index + 1, self.program[index].to_string(), note).unwrap();
+            }
+        }
+
+        output
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+
+        writeln!(dot, "digraph cfg {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=monospace];").unwrap();
+        writeln!(dot, "    exit [shape=doublecircle];").unwrap();
+        if self.successors.iter().flatten().any(|target| *target == Target::OutOfBounds) {
+            writeln!(dot, "    out_of_bounds [shape=octagon];").unwrap();
+        }
+
+        for (b, block) in self.blocks.iter().enumerate() {
+            let mut label = String::new();
+            for index in block.start..block.end {
+                write!(label, "{}: {}\\l", index + 1, self.program[index]).unwrap();
+            }
+
+            let style = if !self.reachable[block.start] {
+                ", style=dashed, color=gray"
+            } else {
+                match self.loop_of(b) {
+                    Some(l) if self.is_infinite_loop(l) => ", style=filled, fillcolor=lightpink",
+                    Some(_) => ", style=filled, fillcolor=lightyellow",
+                    None => ""
+                }
+            };
+
+            writeln!(dot, "    {} [label=\"{}\"{}];", self.target_name(Target::Line(block.start)), label, style).unwrap();
+        }
+
+        for block in self.blocks.iter() {
+            for target in block.successors.iter() {
+                writeln!(dot, "    {} -> {};", self.target_name(Target::Line(block.start)), self.target_name(*target)).unwrap();
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+
+        dot
+    }
+
+    fn loop_of(&self, block: usize) -> Option<usize> {
+        self.loops.iter().position(|l| l.contains(&block))
+    }
+
+    fn target_name(&self, target: Target) -> String {
+        match target {
+            Target::Line(index) => format!("l{}", index + 1),
+            Target::Exit => String::from("exit"),
+            Target::OutOfBounds => String::from("out_of_bounds")
+        }
+    }
+}
+
+fn successors(index: usize, instruction: &Instruction, end: usize) -> Vec<Target> {
+    let target = |next: isize| {
+        if next < 0 || next as usize > end {
+            Target::OutOfBounds
+        } else if next as usize == end {
+            Target::Exit
+        } else {
+            Target::Line(next as usize)
+        }
+    };
+
+    let following = target(index as isize + 1);
+
+    match instruction {
+        Instruction::Halt => vec![Target::Exit],
+        Instruction::Jump(offset) => vec![target(index as isize + offset)],
+        _ if instruction.is_conditional() => {
+            let jump = target(index as isize + instruction.jump_offset().unwrap());
+            if jump == following { vec![following] } else { vec![following, jump] }
+        },
+        _ => vec![following]
+    }
+}
+
+// Leaders are the first instruction, every jump target and every instruction
+// following a jump or halt; each block runs from one leader to the next.
+fn basic_blocks(program: &[Instruction], successors: &[Vec<Target>]) -> (Vec<BasicBlock>, Vec<usize>) {
+    let mut leaders = BTreeSet::new();
+    if !program.is_empty() {
+        leaders.insert(0);
+    }
+
+    for (index, instruction) in program.iter().enumerate() {
+        let falls_through = successors[index] == [Target::Line(index + 1)]
+            && instruction.jump_offset().is_none();
+
+        if !falls_through && index + 1 < program.len() {
+            leaders.insert(index + 1);
+        }
+
+        for target in successors[index].iter() {
+            if let Target::Line(next) = target {
+                if instruction.jump_offset().is_some() {
+                    leaders.insert(*next);
+                }
+            }
+        }
+    }
+
+    let leaders: Vec<usize> = leaders.into_iter().collect();
+    let mut blocks = Vec::new();
+    let mut block_of = vec![0; program.len()];
+
+    for (b, start) in leaders.iter().enumerate() {
+        let end = leaders.get(b + 1).copied().unwrap_or(program.len());
+        block_of[*start..end].fill(b);
+
+        blocks.push(BasicBlock { start: *start, end, successors: successors[end - 1].clone() });
+    }
+
+    (blocks, block_of)
+}
+
+fn reachable_lines(successors: &[Vec<Target>]) -> Vec<bool> {
+    let mut reachable = vec![false; successors.len()];
+    if successors.is_empty() {
+        return reachable;
+    }
+
+    let mut queue = VecDeque::new();
+    queue.push_back(0);
+    reachable[0] = true;
+
+    while let Some(index) = queue.pop_front() {
+        for target in successors[index].iter() {
+            if let Target::Line(next) = target {
+                if !reachable[*next] {
+                    reachable[*next] = true;
+                    queue.push_back(*next);
+                }
+            }
+        }
+    }
+
+    reachable
+}
+
+// Walks the control flow backwards from the end of the program, so every
+// instruction is visited once.
+fn terminating_lines(successors: &[Vec<Target>]) -> Vec<bool> {
+    let mut predecessors = vec![Vec::new(); successors.len()];
+    let mut stack = Vec::new();
+
+    for (index, targets) in successors.iter().enumerate() {
+        for target in targets {
+            match target {
+                Target::Line(next) => predecessors[*next].push(index),
+                Target::Exit => stack.push(index),
+                Target::OutOfBounds => { }
+            }
+        }
+    }
+
+    let mut terminating = vec![false; successors.len()];
+
+    while let Some(index) = stack.pop() {
+        if !terminating[index] {
+            terminating[index] = true;
+            stack.extend(predecessors[index].iter());
+        }
+    }
+
+    terminating
+}
+
+// Strongly connected components of the block graph (Kosaraju), keeping the
+// ones that actually cycle: more than one block, or a block jumping to itself.
+fn loops(blocks: &[BasicBlock], block_of: &[usize]) -> Vec<Vec<usize>> {
+    let edges: Vec<Vec<usize>> = blocks.iter()
+        .map(|block| block.successors.iter()
+            .filter_map(|target| match target {
+                Target::Line(next) => Some(block_of[*next]),
+                _ => None
+            })
+            .collect())
+        .collect();
+
+    let mut reverse_edges = vec![Vec::new(); blocks.len()];
+    for (from, targets) in edges.iter().enumerate() {
+        for to in targets {
+            reverse_edges[*to].push(from);
+        }
+    }
+
+    let mut order = Vec::new();
+    let mut visited = vec![false; blocks.len()];
+    for start in 0..blocks.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+        let mut stack = vec![(start, 0)];
+        while let Some((block, next)) = stack.pop() {
+            if let Some(to) = edges[block].get(next) {
+                stack.push((block, next + 1));
+                if !visited[*to] {
+                    visited[*to] = true;
+                    stack.push((*to, 0));
+                }
+            } else {
+                order.push(block);
+            }
+        }
+    }
+
+    let mut component = vec![usize::MAX; blocks.len()];
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    for start in order.into_iter().rev() {
+        if component[start] != usize::MAX {
+            continue;
+        }
+
+        let id = components.len();
+        let mut members = Vec::new();
+        let mut stack = vec![start];
+        component[start] = id;
+
+        while let Some(block) = stack.pop() {
+            members.push(block);
+            for from in reverse_edges[block].iter() {
+                if component[*from] == usize::MAX {
+                    component[*from] = id;
+                    stack.push(*from);
+                }
+            }
+        }
+
+        members.sort_unstable();
+        components.push(members);
+    }
+
+    let mut loops: Vec<Vec<usize>> = components.into_iter()
+        .filter(|members| members.len() > 1 || edges[members[0]].contains(&members[0]))
+        .collect();
+    loops.sort();
+
+    loops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    fn example_program() -> Vec<Instruction> {
+        assemble("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6").unwrap()
+    }
+
+    #[test]
+    fn example_blocks() {
+        let cfg = ControlFlowGraph::new(&example_program());
+
+        assert_eq!(vec![
+            BasicBlock { start: 0, end: 1, successors: vec![Target::Line(1)] },
+            BasicBlock { start: 1, end: 3, successors: vec![Target::Line(6)] },
+            BasicBlock { start: 3, end: 5, successors: vec![Target::Line(1)] },
+            BasicBlock { start: 5, end: 6, successors: vec![Target::Line(6)] },
+            BasicBlock { start: 6, end: 8, successors: vec![Target::Line(3)] },
+            BasicBlock { start: 8, end: 9, successors: vec![Target::Exit] }
+        ], cfg.blocks());
+    }
+
+    #[test]
+    fn example_analysis() {
+        let cfg = ControlFlowGraph::new(&example_program());
+
+        assert_eq!(vec![6, 9], cfg.unreachable_lines());
+        assert_eq!(&[vec![1, 2, 4]], cfg.loops());
+        assert!(cfg.is_infinite_loop(0));
+        assert!(cfg.can_terminate(8));
+        assert!(!cfg.can_terminate(0));
+    }
+
+    #[test]
+    fn conditional_loop_can_terminate() {
+        let cfg = ControlFlowGraph::new(&assemble("set b +3\nloop: add b -1\njnz b loop\nhlt").unwrap());
+
+        assert_eq!(&[vec![1]], cfg.loops());
+        assert!(!cfg.is_infinite_loop(0));
+        assert!(cfg.unreachable_lines().is_empty());
+    }
+
+    #[test]
+    fn dot_export() {
+        let dot = ControlFlowGraph::new(&example_program()).to_dot();
+
+        assert!(dot.contains("l2 -> l7;"));
+        assert!(dot.contains("l9 -> exit;"));
+        assert!(dot.contains("l6 [label=\"6: acc -99\\l\", style=dashed, color=gray];"));
+    }
+}
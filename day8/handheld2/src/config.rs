@@ -0,0 +1,97 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::boot::Instruction;
+use crate::machine::DEFAULT_STEP_LIMIT;
+
+pub struct Config {
+    pub filename: String,
+    pub mode: Mode,
+    pub step_limit: Option<usize>
+}
+
+pub enum Mode {
+    Repair,
+    Debug,
+    Disassemble,
+    ControlFlow,
+    ControlFlowDot,
+    Trace { output: String, patch: Option<(usize, Instruction)> },
+    Replay { other: Option<String> }
+}
+
+impl Config {
+    // `--step-limit N` can go anywhere after the mode; `--step-limit none`
+    // lets programs run until they halt or loop.
+    pub fn new(args: &[String]) -> Result<Config, ParseConfigError> {
+        let mut step_limit = Some(DEFAULT_STEP_LIMIT);
+        let mut positional = Vec::new();
+
+        let mut options = args.iter();
+        while let Some(arg) = options.next() {
+            if arg == "--step-limit" {
+                let value = options.next().ok_or(ParseConfigError::NotEnoughArguments)?;
+                step_limit = match value.as_str() {
+                    "none" => None,
+                    value => Some(value.parse::<usize>().map_err(|_| ParseConfigError::InvalidStepLimit)?)
+                };
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+
+        let args = positional.as_slice();
+        if args.len() < 2 {
+            return Err(ParseConfigError::NotEnoughArguments);
+        }
+
+        let mode = match args[1].as_str() {
+            "debug" => Mode::Debug,
+            "disasm" => Mode::Disassemble,
+            "cfg" => Mode::ControlFlow,
+            "dot" => Mode::ControlFlowDot,
+            "trace" => {
+                let output = args.get(3).cloned().ok_or(ParseConfigError::NotEnoughArguments)?;
+                let patch = match (args.get(4), args.get(5)) {
+                    (Some(line_number), Some(instruction)) => {
+                        let line_number = line_number.parse::<usize>()
+                            .map_err(|_| ParseConfigError::InvalidPatch)?;
+                        let instruction = instruction.parse::<Instruction>()
+                            .map_err(|_| ParseConfigError::InvalidPatch)?;
+
+                        Some((line_number, instruction))
+                    },
+                    (Some(_), None) => return Err(ParseConfigError::InvalidPatch),
+                    _ => None
+                };
+
+                Mode::Trace { output, patch }
+            },
+            "replay" => Mode::Replay { other: args.get(3).cloned() },
+            filename => return Ok(Config { filename: String::from(filename), mode: Mode::Repair, step_limit })
+        };
+
+        let filename = args.get(2).cloned().ok_or(ParseConfigError::NotEnoughArguments)?;
+
+        Ok(Config { filename, mode, step_limit })
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseConfigError {
+    NotEnoughArguments,
+    InvalidPatch,
+    InvalidStepLimit
+}
+
+impl Display for ParseConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            ParseConfigError::NotEnoughArguments => "not enough arguments",
+            ParseConfigError::InvalidPatch => "patch must be a line number followed by an instruction, e.g. 414 \"nop -179\"",
+            ParseConfigError::InvalidStepLimit => "step limit must be a whole number or none"
+        })
+    }
+}
+
+impl Error for ParseConfigError { }
@@ -0,0 +1,282 @@
+use std::error::Error;
+use std::fmt::{Display, Write as FmtWrite};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::boot::{Instruction, Register};
+use crate::machine::{Machine, Status, Stop};
+
+#[derive(Debug, PartialEq)]
+pub enum DebugCommand {
+    Step(usize),
+    Continue,
+    Break(usize),
+    Delete(usize),
+    Watch,
+    Unwatch,
+    Print(PrintTarget),
+    Patch(usize, Instruction),
+    List,
+    Reset,
+    Help,
+    Quit
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PrintTarget {
+    Register(Register),
+    ProgramCounter,
+    Breakpoints
+}
+
+impl FromStr for DebugCommand {
+    type Err = ParseDebugCommandError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (command, argument) = match s.split_once(' ') {
+            Some((command, argument)) => (command, argument.trim()),
+            None => (s, "")
+        };
+
+        let line_number = || argument.parse::<usize>()
+            .map_err(|_| ParseDebugCommandError::InvalidLineNumber(String::from(argument)));
+
+        match (command, argument) {
+            ("step", "") | ("s", "") => Ok(DebugCommand::Step(1)),
+            ("step", _) | ("s", _) => Ok(DebugCommand::Step(argument.parse::<usize>()
+                .map_err(|_| ParseDebugCommandError::InvalidCount(String::from(argument)))?)),
+            ("continue", "") | ("c", "") => Ok(DebugCommand::Continue),
+            ("break", _) | ("b", _) => Ok(DebugCommand::Break(line_number()?)),
+            ("delete", _) => Ok(DebugCommand::Delete(line_number()?)),
+            ("watch", "acc") => Ok(DebugCommand::Watch),
+            ("unwatch", "acc") => Ok(DebugCommand::Unwatch),
+            ("print", "pc") | ("p", "pc") => Ok(DebugCommand::Print(PrintTarget::ProgramCounter)),
+            ("print", "breakpoints") | ("p", "breakpoints") => Ok(DebugCommand::Print(PrintTarget::Breakpoints)),
+            ("print", _) | ("p", _) => Ok(DebugCommand::Print(PrintTarget::Register(argument.parse::<Register>()
+                .map_err(|_| ParseDebugCommandError::UnknownCommand(String::from(s)))?))),
+            ("patch", _) => {
+                let (line, instruction) = argument.split_once(' ')
+                    .ok_or_else(|| ParseDebugCommandError::InvalidInstruction(String::from(argument)))?;
+                let line_number = line.parse::<usize>()
+                    .map_err(|_| ParseDebugCommandError::InvalidLineNumber(String::from(line)))?;
+                let instruction = instruction.trim().parse::<Instruction>()
+                    .map_err(|_| ParseDebugCommandError::InvalidInstruction(String::from(instruction)))?;
+
+                Ok(DebugCommand::Patch(line_number, instruction))
+            },
+            ("list", "") | ("l", "") => Ok(DebugCommand::List),
+            ("reset", "") => Ok(DebugCommand::Reset),
+            ("help", "") | ("h", "") => Ok(DebugCommand::Help),
+            ("quit", "") | ("q", "") => Ok(DebugCommand::Quit),
+            _ => Err(ParseDebugCommandError::UnknownCommand(String::from(s)))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseDebugCommandError {
+    UnknownCommand(String),
+    InvalidCount(String),
+    InvalidLineNumber(String),
+    InvalidInstruction(String)
+}
+
+impl Display for ParseDebugCommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            ParseDebugCommandError::UnknownCommand(command) => format!("unknown command: {}", command),
+            ParseDebugCommandError::InvalidCount(count) => format!("invalid step count: {}", count),
+            ParseDebugCommandError::InvalidLineNumber(line) => format!("invalid line number: {}", line),
+            ParseDebugCommandError::InvalidInstruction(instruction) => format!("invalid instruction: {}", instruction)
+        })
+    }
+}
+
+impl Error for ParseDebugCommandError { }
+
+const HELP: &str = "\
+step [n]            execute n instructions (default 1)
+continue            run until a breakpoint, watch or halt
+break <line>        set a breakpoint
+delete <line>       remove a breakpoint
+watch acc           stop whenever acc changes
+unwatch acc         stop watching acc
+print acc|b|c|d|pc|breakpoints
+patch <line> <instruction>
+list                show the instructions around the program counter
+reset               restart the program, keeping patches and breakpoints
+quit";
+
+pub struct Debugger {
+    machine: Machine
+}
+
+impl Debugger {
+    pub fn new(machine: Machine) -> Debugger {
+        Debugger { machine }
+    }
+
+    pub fn machine(&self) -> &Machine {
+        &self.machine
+    }
+
+    pub fn execute(&mut self, command: &DebugCommand) -> String {
+        let mut output = String::new();
+
+        match command {
+            DebugCommand::Step(count) => {
+                for _ in 0..*count {
+                    if self.machine.step() != Status::Running {
+                        break;
+                    }
+                }
+
+                output.push_str(&self.location());
+            },
+            DebugCommand::Continue => {
+                let stop = self.machine.resume();
+                writeln!(output, "{}", stop).unwrap();
+
+                if let Stop::Halted(_) = stop {
+                    write!(output, "acc = {}", self.machine.accumulator()).unwrap();
+                } else {
+                    output.push_str(&self.location());
+                }
+            },
+            DebugCommand::Break(line_number) => {
+                match self.machine.add_breakpoint(*line_number) {
+                    Ok(()) => write!(output, "breakpoint set at line {}", line_number).unwrap(),
+                    Err(e) => write!(output, "{}", e).unwrap()
+                }
+            },
+            DebugCommand::Delete(line_number) => {
+                if self.machine.remove_breakpoint(*line_number) {
+                    write!(output, "breakpoint removed from line {}", line_number).unwrap();
+                } else {
+                    write!(output, "no breakpoint at line {}", line_number).unwrap();
+                }
+            },
+            DebugCommand::Watch => {
+                self.machine.set_watch_accumulator(true);
+                output.push_str("watching acc");
+            },
+            DebugCommand::Unwatch => {
+                self.machine.set_watch_accumulator(false);
+                output.push_str("no longer watching acc");
+            },
+            DebugCommand::Print(PrintTarget::Register(register)) => {
+                write!(output, "{} = {}", register, self.machine.register(*register)).unwrap();
+            },
+            DebugCommand::Print(PrintTarget::ProgramCounter) => {
+                write!(output, "pc = line {} ({})", self.machine.program_counter() + 1, self.machine.status()).unwrap();
+            },
+            DebugCommand::Print(PrintTarget::Breakpoints) => {
+                let breakpoints: Vec<String> = self.machine.breakpoints().iter().map(|b| b.to_string()).collect();
+                write!(output, "breakpoints: {}", breakpoints.join(", ")).unwrap();
+            },
+            DebugCommand::Patch(line_number, instruction) => {
+                match self.machine.patch(*line_number, instruction.clone()) {
+                    Ok(original) => write!(output, "line {}: {} -> {}", line_number, original, instruction).unwrap(),
+                    Err(e) => write!(output, "{}", e).unwrap()
+                }
+            },
+            DebugCommand::List => {
+                let start = self.machine.program_counter().saturating_sub(3);
+                for line in self.machine.program().iter().skip(start).take(7) {
+                    let marker = if line.line_number == self.machine.program_counter() + 1 { ">" } else { " " };
+                    writeln!(output, "{} {:>5}  {}", marker, line.line_number, line.instruction).unwrap();
+                }
+            },
+            DebugCommand::Reset => {
+                self.machine.reset();
+                output.push_str(&self.location());
+            },
+            DebugCommand::Help => output.push_str(HELP),
+            DebugCommand::Quit => { }
+        }
+
+        output.trim_end().to_string()
+    }
+
+    pub fn repl<R, W>(&mut self, input: R, mut output: W) -> io::Result<()>
+    where R: BufRead, W: Write, {
+        writeln!(output, "{}", self.location())?;
+        write!(output, "(handheld) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+
+            if !line.trim().is_empty() {
+                match line.parse::<DebugCommand>() {
+                    Ok(DebugCommand::Quit) => return Ok(()),
+                    Ok(command) => writeln!(output, "{}", self.execute(&command))?,
+                    Err(e) => write!(output, "{}", e)?
+                }
+            }
+
+            write!(output, "(handheld) ")?;
+            output.flush()?;
+        }
+
+        writeln!(output)
+    }
+
+    fn location(&self) -> String {
+        match self.machine.current_line() {
+            Some(line) if self.machine.status() == Status::Running => {
+                format!("line {}: {} (acc = {})", line.line_number, line.instruction, self.machine.accumulator())
+            },
+            _ => format!("{} (acc = {})", self.machine.status(), self.machine.accumulator())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(Ok(DebugCommand::Step(1)), "step".parse::<DebugCommand>());
+        assert_eq!(Ok(DebugCommand::Step(5)), "step 5".parse::<DebugCommand>());
+        assert_eq!(Ok(DebugCommand::Break(123)), "break 123".parse::<DebugCommand>());
+        assert_eq!(Ok(DebugCommand::Print(PrintTarget::Register(Register::Acc))), "print acc".parse::<DebugCommand>());
+        assert_eq!(Ok(DebugCommand::Print(PrintTarget::Register(Register::B))), "print b".parse::<DebugCommand>());
+        assert_eq!(Ok(DebugCommand::Patch(42, Instruction::Noop(0))), "patch 42 nop +0".parse::<DebugCommand>());
+        assert!("jump 3".parse::<DebugCommand>().is_err());
+    }
+
+    #[test]
+    fn pc_is_printed_as_a_line_number() {
+        let program = vec!["nop +0", "acc +1", "jmp -2"]
+            .into_iter()
+            .map(|line| line.parse::<Instruction>().unwrap())
+            .collect();
+        let mut debugger = Debugger::new(Machine::new(program));
+
+        assert!(debugger.execute(&DebugCommand::Step(1)).starts_with("line 2: acc +1"));
+        assert!(debugger.execute(&DebugCommand::Print(PrintTarget::ProgramCounter)).starts_with("pc = line 2 "));
+        assert!(debugger.execute(&DebugCommand::List).contains(">     2  acc +1"));
+    }
+
+    #[test]
+    fn repl_session() {
+        let program = vec!["nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3", "acc -99", "acc +1", "jmp -4", "acc +6"]
+            .into_iter()
+            .map(|line| line.parse::<Instruction>().unwrap())
+            .collect();
+        let mut debugger = Debugger::new(Machine::new(program));
+
+        let input = "break 8\ncontinue\npatch 8 nop -4\ncontinue\nprint acc\nquit\n";
+        let mut output = Vec::new();
+        debugger.repl(input.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("breakpoint at line 8"));
+        assert!(output.contains("line 8: jmp -4 -> nop -4"));
+        assert!(output.contains("terminated"));
+        assert_eq!(8, debugger.machine().accumulator());
+    }
+}
@@ -1,48 +1,85 @@
-#![feature(str_split_once)]
-
 use std::error::Error;
-use std::fs::{File};
-use std::io::{self, BufRead};
+use std::fs::{File, read_to_string};
+use std::io::{self, BufReader};
 use std::path::Path;
 
-use boot::{Instruction, run_program};
+use assembler::{assemble, disassemble_with_labels};
+use boot::{Instruction, repair_program};
+use cfg::ControlFlowGraph;
+use config::{Config, Mode};
+use debugger::Debugger;
+use machine::Machine;
+use trace::{Trace, render_diff};
 
-mod boot;
+pub mod assembler;
+pub mod boot;
+pub mod cfg;
+pub mod config;
+pub mod debugger;
+pub mod machine;
+pub mod trace;
 
-pub fn run(config: Config) -> Result<isize, Box<dyn Error>> {
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
     let filename = config.filename;
 
-    let mut program = Vec::new();
+    match config.mode {
+        Mode::Repair => {
+            let repair = repair_program(&read_program(&filename)?)?;
 
-    let lines = read_lines(filename)?;
-    for line in lines {
-        program.push(line?.parse::<Instruction>()?);
-    }
+            Ok(repair.to_string())
+        },
+        Mode::Debug => {
+            let mut machine = Machine::new(read_program(&filename)?);
+            machine.set_step_limit(config.step_limit);
 
-    let result = run_program(&program);
+            let mut debugger = Debugger::new(machine);
 
-    Ok(result)
-}
+            let stdin = io::stdin();
+            debugger.repl(stdin.lock(), io::stdout())?;
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
+            let machine = debugger.machine();
+            Ok(format!("{} (acc = {})", machine.status(), machine.accumulator()))
+        },
+        Mode::Disassemble => Ok(disassemble_with_labels(&read_program(&filename)?)),
+        Mode::ControlFlow => Ok(ControlFlowGraph::new(&read_program(&filename)?).listing()),
+        Mode::ControlFlowDot => Ok(ControlFlowGraph::new(&read_program(&filename)?).to_dot()),
+        Mode::Trace { output, patch } => {
+            let mut machine = Machine::new(read_program(&filename)?);
+            machine.set_step_limit(config.step_limit);
+            if let Some((line_number, instruction)) = patch {
+                machine.patch(line_number, instruction)?;
+            }
 
-pub struct Config {
-    pub filename: String
-}
+            let trace = Trace::record(&mut machine);
+            trace.write(File::create(&output)?)?;
 
-impl Config {
-    pub fn new(args: &[String]) -> Result<Config, &'static str> {
-        if args.len() < 2 {
-            return Err("not enough arguments");
-        }
+            Ok(format!("recorded {} steps to {} ({})", trace.steps().len(), output, trace.outcome()))
+        },
+        Mode::Replay { other } => {
+            let left = read_trace(&filename)?;
+
+            match other {
+                Some(other) => Ok(render_diff(&left, &read_trace(other)?)),
+                None => {
+                    let mut output = Vec::new();
+                    left.write(&mut output)?;
 
-        let filename = args[1].clone();
-    
-        Ok(Config { filename })
+                    Ok(String::from_utf8(output)?)
+                }
+            }
+        }
     }
 }
 
+pub fn read_program<P>(filename: P) -> Result<Vec<Instruction>, Box<dyn Error>>
+where P: AsRef<Path>, {
+    let source = read_to_string(filename)?;
+
+    Ok(assemble(&source)?)
+}
+
+fn read_trace<P>(filename: P) -> Result<Trace, Box<dyn Error>>
+where P: AsRef<Path>, {
+    let file = File::open(filename)?;
+    Trace::read(BufReader::new(file))
+}
@@ -0,0 +1,361 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::boot::{Instruction, Line, Register};
+
+// Programs with conditional jumps can run forever without ever repeating a
+// state (a counter that keeps going up, say), so by default a machine gives up
+// after this many instructions.
+pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
+pub struct Machine {
+    program: Vec<Line>,
+    conditional: bool,
+    registers: [isize; Register::COUNT],
+    program_counter: usize,
+    out_of_bounds: bool,
+    executed_states: HashSet<(usize, [isize; Register::COUNT])>,
+    steps: usize,
+    step_limit: Option<usize>,
+    breakpoints: HashSet<usize>,
+    watch_accumulator: bool
+}
+
+impl Machine {
+    pub fn new(program: Vec<Instruction>) -> Machine {
+        let program: Vec<Line> = program.into_iter()
+            .enumerate()
+            .map(|(index, instruction)| Line { line_number: index + 1, instruction })
+            .collect();
+        let conditional = program.iter().any(|line| line.instruction.is_conditional());
+
+        Machine {
+            program,
+            conditional,
+            registers: [0; Register::COUNT],
+            program_counter: 0,
+            out_of_bounds: false,
+            executed_states: HashSet::new(),
+            steps: 0,
+            step_limit: Some(DEFAULT_STEP_LIMIT),
+            breakpoints: HashSet::new(),
+            watch_accumulator: false
+        }
+    }
+
+    pub fn accumulator(&self) -> isize {
+        self.register(Register::Acc)
+    }
+
+    pub fn register(&self, register: Register) -> isize {
+        self.registers[register.index()]
+    }
+
+    pub fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    pub fn program(&self) -> &[Line] {
+        &self.program
+    }
+
+    // Instructions executed since the machine was created or reset.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    // None lets the machine run for as long as it takes to halt or loop.
+    pub fn set_step_limit(&mut self, step_limit: Option<usize>) {
+        self.step_limit = step_limit;
+    }
+
+    pub fn current_line(&self) -> Option<&Line> {
+        if self.out_of_bounds {
+            return None;
+        }
+
+        self.program.get(self.program_counter)
+    }
+
+    pub fn status(&self) -> Status {
+        if self.out_of_bounds || self.program_counter > self.program.len() {
+            Status::OutOfBounds
+        } else if self.program_counter == self.program.len() {
+            Status::Terminated
+        } else if self.executed_states.contains(&self.state()) {
+            Status::Looped(self.program_counter + 1)
+        } else if let Some(limit) = self.step_limit.filter(|limit| self.steps >= *limit) {
+            Status::StepLimit(limit)
+        } else {
+            Status::Running
+        }
+    }
+
+    // Executes the instruction at the program counter, unless the machine
+    // has already halted.
+    pub fn step(&mut self) -> Status {
+        let status = self.status();
+        if status != Status::Running {
+            return status;
+        }
+
+        self.executed_states.insert(self.state());
+        self.steps += 1;
+
+        // Registers wrap around like the handheld's would, rather than
+        // stopping the machine.
+        let instruction = &self.program[self.program_counter].instruction;
+        match instruction {
+            Instruction::Acc(value) => {
+                let acc = &mut self.registers[Register::Acc.index()];
+                *acc = acc.wrapping_add(*value);
+            },
+            Instruction::Add(register, value) => {
+                let register = &mut self.registers[register.index()];
+                *register = register.wrapping_add(*value);
+            },
+            Instruction::Set(register, value) => self.registers[register.index()] = *value,
+            _ => { }
+        }
+
+        match instruction.next(self.program_counter, self.program.len(), &self.registers) {
+            Some(next) => self.program_counter = next,
+            None => self.out_of_bounds = true
+        }
+
+        self.status()
+    }
+
+    // Runs until the machine halts, ignoring breakpoints and watches.
+    pub fn run(&mut self) -> Status {
+        while self.step() == Status::Running { }
+
+        self.status()
+    }
+
+    // Runs until the machine halts, reaches a breakpoint or changes a
+    // watched accumulator. Always executes at least one instruction, so
+    // resuming from a breakpoint moves past it.
+    pub fn resume(&mut self) -> Stop {
+        loop {
+            let line_number = self.program_counter + 1;
+            let before = self.accumulator();
+
+            let status = self.step();
+            if status != Status::Running {
+                return Stop::Halted(status);
+            }
+
+            if self.watch_accumulator && self.accumulator() != before {
+                return Stop::Watch { line_number, old: before, new: self.accumulator() };
+            }
+
+            if self.breakpoints.contains(&(self.program_counter + 1)) {
+                return Stop::Breakpoint(self.program_counter + 1);
+            }
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, line_number: usize) -> Result<(), MachineError> {
+        self.check_line_number(line_number)?;
+        self.breakpoints.insert(line_number);
+
+        Ok(())
+    }
+
+    pub fn remove_breakpoint(&mut self, line_number: usize) -> bool {
+        self.breakpoints.remove(&line_number)
+    }
+
+    pub fn breakpoints(&self) -> Vec<usize> {
+        let mut breakpoints: Vec<usize> = self.breakpoints.iter().cloned().collect();
+        breakpoints.sort_unstable();
+
+        breakpoints
+    }
+
+    pub fn set_watch_accumulator(&mut self, watch: bool) {
+        self.watch_accumulator = watch;
+    }
+
+    pub fn patch(&mut self, line_number: usize, instruction: Instruction) -> Result<Instruction, MachineError> {
+        self.check_line_number(line_number)?;
+
+        let line = &mut self.program[line_number - 1];
+        let original = std::mem::replace(&mut line.instruction, instruction);
+        self.conditional = self.program.iter().any(|line| line.instruction.is_conditional());
+
+        Ok(original)
+    }
+
+    pub fn reset(&mut self) {
+        self.registers = [0; Register::COUNT];
+        self.program_counter = 0;
+        self.out_of_bounds = false;
+        self.executed_states.clear();
+        self.steps = 0;
+    }
+
+    // Without conditional jumps control flow never depends on the registers,
+    // so revisiting a line is already a loop (the original boot code rule).
+    // Otherwise only a repeat of the whole machine state is.
+    fn state(&self) -> (usize, [isize; Register::COUNT]) {
+        if self.conditional {
+            (self.program_counter, self.registers)
+        } else {
+            (self.program_counter, [0; Register::COUNT])
+        }
+    }
+
+    fn check_line_number(&self, line_number: usize) -> Result<(), MachineError> {
+        if line_number == 0 || line_number > self.program.len() {
+            return Err(MachineError::NoSuchLine(line_number));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Running,
+    Terminated,
+    Looped(usize),
+    OutOfBounds,
+    StepLimit(usize)
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Running => write!(f, "running"),
+            Status::Terminated => write!(f, "terminated"),
+            Status::Looped(line_number) => write!(f, "loop detected at line {}", line_number),
+            Status::OutOfBounds => write!(f, "jumped out of bounds"),
+            Status::StepLimit(limit) => write!(f, "step limit of {} reached", limit)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stop {
+    Halted(Status),
+    Breakpoint(usize),
+    Watch { line_number: usize, old: isize, new: isize }
+}
+
+impl Display for Stop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stop::Halted(status) => write!(f, "{}", status),
+            Stop::Breakpoint(line_number) => write!(f, "breakpoint at line {}", line_number),
+            Stop::Watch { line_number, old, new } => write!(f, "acc changed at line {}: {} -> {}", line_number, old, new)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MachineError {
+    NoSuchLine(usize)
+}
+
+impl Display for MachineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            MachineError::NoSuchLine(line_number) => format!("no such line: {}", line_number)
+        })
+    }
+}
+
+impl Error for MachineError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_program() -> Vec<Instruction> {
+        vec![
+            "nop +0", "acc +1", "jmp +4", "acc +3", "jmp -3",
+            "acc -99", "acc +1", "jmp -4", "acc +6"
+        ].into_iter().map(|line| line.parse::<Instruction>().unwrap()).collect()
+    }
+
+    #[test]
+    fn run_until_loop() {
+        let mut machine = Machine::new(example_program());
+
+        assert_eq!(Status::Looped(2), machine.run());
+        assert_eq!(5, machine.accumulator());
+    }
+
+    #[test]
+    fn breakpoints_and_watch() {
+        let mut machine = Machine::new(example_program());
+        machine.add_breakpoint(7).unwrap();
+
+        assert_eq!(Stop::Breakpoint(7), machine.resume());
+        assert_eq!(1, machine.accumulator());
+
+        machine.set_watch_accumulator(true);
+        assert_eq!(Stop::Watch { line_number: 7, old: 1, new: 2 }, machine.resume());
+
+        assert_eq!(Err(MachineError::NoSuchLine(10)), machine.add_breakpoint(10));
+    }
+
+    #[test]
+    fn registers_and_halt() {
+        let program = vec!["set b +3", "acc +2", "add b -1", "jnz b -2", "hlt", "acc +100"]
+            .into_iter()
+            .map(|line| line.parse::<Instruction>().unwrap())
+            .collect();
+        let mut machine = Machine::new(program);
+
+        assert_eq!(Status::Terminated, machine.run());
+        assert_eq!(6, machine.accumulator());
+        assert_eq!(0, machine.register(Register::B));
+    }
+
+    #[test]
+    fn patch_and_reset() {
+        let mut machine = Machine::new(example_program());
+
+        assert_eq!(Ok(Instruction::Jump(-4)), machine.patch(8, "nop -4".parse().unwrap()));
+        assert_eq!(Status::Terminated, machine.run());
+        assert_eq!(8, machine.accumulator());
+
+        machine.reset();
+        assert_eq!(0, machine.accumulator());
+        assert_eq!(Status::Running, machine.status());
+    }
+
+    #[test]
+    fn counting_loop_stops_at_step_limit() {
+        // The counter never repeats a state, so only the step limit stops it.
+        let program = vec!["add b +1", "jnz b -1"]
+            .into_iter()
+            .map(|line| line.parse::<Instruction>().unwrap())
+            .collect();
+        let mut machine = Machine::new(program);
+        machine.set_step_limit(Some(1000));
+
+        assert_eq!(Status::StepLimit(1000), machine.run());
+        assert_eq!(1000, machine.steps());
+        assert_eq!(500, machine.register(Register::B));
+
+        machine.reset();
+        assert_eq!(Status::Running, machine.status());
+    }
+
+    #[test]
+    fn registers_wrap_around() {
+        let program = vec![format!("set b +{}", isize::MAX), String::from("add b +1"), String::from("acc -1")]
+            .into_iter()
+            .map(|line| line.parse::<Instruction>().unwrap())
+            .collect();
+        let mut machine = Machine::new(program);
+
+        assert_eq!(Status::Terminated, machine.run());
+        assert_eq!(isize::MIN, machine.register(Register::B));
+    }
+}
@@ -1,7 +1,7 @@
 use std::env;
 use std::process;
 
-use handheld::{run, Config};
+use handheld::{config::Config, run};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
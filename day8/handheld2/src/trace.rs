@@ -0,0 +1,217 @@
+use std::error::Error;
+use std::fmt::{Display, Write as FmtWrite};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::boot::Instruction;
+use crate::machine::{Machine, Status};
+
+// One executed instruction, stored as `line before after instruction`, e.g.
+// `7 1 2 acc +1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    pub line_number: usize,
+    pub instruction: Instruction,
+    pub before: isize,
+    pub after: isize
+}
+
+impl Display for TraceStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {} {}", self.line_number, self.before, self.after, self.instruction)
+    }
+}
+
+impl FromStr for TraceStep {
+    type Err = ParseTraceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseTraceError::InvalidStep(String::from(s));
+
+        let mut parts = s.splitn(4, ' ');
+        let line_number = parts.next().and_then(|part| part.parse::<usize>().ok()).ok_or_else(invalid)?;
+        let before = parts.next().and_then(|part| part.parse::<isize>().ok()).ok_or_else(invalid)?;
+        let after = parts.next().and_then(|part| part.parse::<isize>().ok()).ok_or_else(invalid)?;
+        let instruction = parts.next().and_then(|part| part.parse::<Instruction>().ok()).ok_or_else(invalid)?;
+
+        Ok(TraceStep { line_number, instruction, before, after })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Trace {
+    steps: Vec<TraceStep>,
+    outcome: String
+}
+
+impl Trace {
+    // Runs the machine until it halts, recording every instruction executed.
+    // A machine that hits its step limit halts too, so the trace holds at most
+    // that many steps and ends with a step limit outcome.
+    pub fn record(machine: &mut Machine) -> Trace {
+        let mut steps = Vec::new();
+        let mut status = machine.status();
+
+        while status == Status::Running {
+            let line = machine.current_line().unwrap();
+            let line_number = line.line_number;
+            let instruction = line.instruction.clone();
+            let before = machine.accumulator();
+
+            status = machine.step();
+
+            steps.push(TraceStep { line_number, instruction, before, after: machine.accumulator() });
+        }
+
+        Trace { steps, outcome: status.to_string() }
+    }
+
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    pub fn outcome(&self) -> &str {
+        &self.outcome
+    }
+
+    pub fn write<W: Write>(&self, mut output: W) -> io::Result<()> {
+        for step in self.steps.iter() {
+            writeln!(output, "{}", step)?;
+        }
+        writeln!(output, "# {}", self.outcome)
+    }
+
+    pub fn read<R: BufRead>(input: R) -> Result<Trace, Box<dyn Error>> {
+        let mut steps = Vec::new();
+        let mut outcome = String::new();
+
+        for line in input.lines() {
+            let line = line?;
+
+            if let Some(comment) = line.strip_prefix('#') {
+                outcome = String::from(comment.trim());
+            } else if !line.trim().is_empty() {
+                steps.push(line.parse::<TraceStep>()?);
+            }
+        }
+
+        Ok(Trace { steps, outcome })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Divergence<'a> {
+    pub step: usize,
+    pub left: Option<&'a TraceStep>,
+    pub right: Option<&'a TraceStep>
+}
+
+// The first step (counting from 1) at which the two traces differ, or None
+// if they executed exactly the same instructions.
+pub fn first_divergence<'a>(left: &'a Trace, right: &'a Trace) -> Option<Divergence<'a>> {
+    let length = left.steps.len().max(right.steps.len());
+
+    (0..length)
+        .find(|index| left.steps.get(*index) != right.steps.get(*index))
+        .map(|index| Divergence {
+            step: index + 1,
+            left: left.steps.get(index),
+            right: right.steps.get(index)
+        })
+}
+
+pub fn render_diff(left: &Trace, right: &Trace) -> String {
+    let mut output = String::new();
+
+    match first_divergence(left, right) {
+        None => {
+            write!(output, "traces are identical ({} steps, {})", left.steps.len(), left.outcome).unwrap();
+        },
+        Some(divergence) => {
+            writeln!(output, "traces diverge at step {}", divergence.step).unwrap();
+
+            if divergence.step > 1 {
+                writeln!(output, "  last common: {}", describe(left.steps.get(divergence.step - 2), left)).unwrap();
+            }
+            writeln!(output, "  left:        {}", describe(divergence.left, left)).unwrap();
+            writeln!(output, "  right:       {}", describe(divergence.right, right)).unwrap();
+            writeln!(output, "left ran {} steps ({})", left.steps.len(), left.outcome).unwrap();
+            write!(output, "right ran {} steps ({})", right.steps.len(), right.outcome).unwrap();
+        }
+    }
+
+    output
+}
+
+fn describe(step: Option<&TraceStep>, trace: &Trace) -> String {
+    match step {
+        Some(step) => format!("line {}: {} (acc {} -> {})", step.line_number, step.instruction, step.before, step.after),
+        None => format!("<{}>", trace.outcome)
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseTraceError {
+    InvalidStep(String)
+}
+
+impl Display for ParseTraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", match self {
+            ParseTraceError::InvalidStep(step) => format!("invalid trace step: {}", step)
+        })
+    }
+}
+
+impl Error for ParseTraceError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::assemble;
+
+    fn example_machine() -> Machine {
+        Machine::new(assemble("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6").unwrap())
+    }
+
+    #[test]
+    fn record_and_read_back() {
+        let trace = Trace::record(&mut example_machine());
+
+        assert_eq!(7, trace.steps().len());
+        assert_eq!(TraceStep { line_number: 4, instruction: Instruction::Acc(3), before: 2, after: 5 }, trace.steps()[5]);
+        assert_eq!("loop detected at line 2", trace.outcome());
+
+        let mut file = Vec::new();
+        trace.write(&mut file).unwrap();
+
+        assert_eq!(trace, Trace::read(file.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn diff_original_and_patched() {
+        let original = Trace::record(&mut example_machine());
+
+        let mut patched = example_machine();
+        patched.patch(8, Instruction::Noop(-4)).unwrap();
+        let patched = Trace::record(&mut patched);
+
+        let divergence = first_divergence(&original, &patched).unwrap();
+        assert_eq!(5, divergence.step);
+        assert_eq!(Some(&TraceStep { line_number: 8, instruction: Instruction::Noop(-4), before: 2, after: 2 }), divergence.right);
+
+        assert_eq!(None, first_divergence(&original, &original));
+    }
+
+    #[test]
+    fn record_stops_at_step_limit() {
+        let mut machine = Machine::new(assemble("acc +1\njnz acc -1").unwrap());
+        machine.set_step_limit(Some(10));
+
+        let trace = Trace::record(&mut machine);
+
+        assert_eq!(10, trace.steps().len());
+        assert_eq!(TraceStep { line_number: 2, instruction: "jnz acc -1".parse().unwrap(), before: 5, after: 5 }, trace.steps()[9]);
+        assert_eq!("step limit of 10 reached", trace.outcome());
+    }
+}
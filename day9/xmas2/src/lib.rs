@@ -1,16 +1,52 @@
 use std::{error::Error, fmt::Display};
 use std::fs::{File};
-use std::io::{self, BufRead};
+use std::io::{self, BufRead, Write};
 use std::path::Path;
 
-use xmas::{find_invalid_number, find_weakness};
+use xmas::{InvalidNumbers, WeaknessRange, find_invalid_number, find_weakness_ranges};
+
+pub mod xmas;
+
+pub fn run(config: Config) -> Result<String, Box<dyn Error>> {
+    match config.mode {
+        Mode::Weakness => {
+            let ranges: Vec<String> = run_weakness(&config.filename, config.preamble_size)?
+                .iter()
+                .map(|range| range.to_string())
+                .collect();
+
+            Ok(ranges.join("\n"))
+        },
+        Mode::Invalid => {
+            let count = if config.filename == "-" {
+                let stdin = io::stdin();
+                report_invalid_numbers(stdin.lock(), config.preamble_size)?
+            } else {
+                report_invalid_numbers(io::BufReader::new(File::open(&config.filename)?), config.preamble_size)?
+            };
+
+            Ok(format!("invalid numbers found: {}", count))
+        }
+    }
+}
 
-mod xmas;
+// Prints every invalid number as soon as it's read, so long streams can be
+// watched as they arrive.
+fn report_invalid_numbers<R: BufRead>(reader: R, preamble_size: usize) -> Result<usize, Box<dyn Error>> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let mut count = 0;
+
+    for invalid_number in InvalidNumbers::new(reader, preamble_size) {
+        writeln!(stdout, "{}", invalid_number?)?;
+        stdout.flush()?;
+        count += 1;
+    }
 
-pub fn run(config: Config) -> Result<i64, Box<dyn Error>> {
-    let filename = config.filename;
-    let preamble_size = config.preamble_size;
+    Ok(count)
+}
 
+fn run_weakness(filename: &str, preamble_size: usize) -> Result<Vec<WeaknessRange>, Box<dyn Error>> {
     let mut data = Vec::new();
 
     let lines = read_lines(filename)?;
@@ -21,10 +57,12 @@ pub fn run(config: Config) -> Result<i64, Box<dyn Error>> {
     let invalid_number = find_invalid_number(&data, preamble_size)
         .ok_or(ApplicationError::CouldntFindInvalidNumber)?;
 
-    let weakness = find_weakness(&data, invalid_number)
-        .ok_or(ApplicationError::CouldntFindWeakness)?;
+    let ranges = find_weakness_ranges(&data, invalid_number);
+    if ranges.is_empty() {
+        return Err(Box::new(ApplicationError::CouldntFindWeakness));
+    }
 
-    Ok(weakness)
+    Ok(ranges)
 }
 
 #[derive(Debug)]
@@ -44,11 +82,6 @@ impl Display for ApplicationError {
 
 impl Error for ApplicationError { }
 
-#[derive(Debug)]
-enum ApplicationErrorKind {
-
-}
-
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
 where P: AsRef<Path>, {
     let file = File::open(filename)?;
@@ -57,7 +90,13 @@ where P: AsRef<Path>, {
 
 pub struct Config {
     pub filename: String,
-    pub preamble_size: usize
+    pub preamble_size: usize,
+    pub mode: Mode
+}
+
+pub enum Mode {
+    Weakness,
+    Invalid
 }
 
 impl Config {
@@ -71,8 +110,13 @@ impl Config {
             Ok(v) => v,
             Err(_) => { return Err(ParseConfigError { kind: ParseConfigErrorKind::InvalidPreambleSize }); }
         };
+        let mode = match args.get(3).map(|mode| mode.as_str()) {
+            None => Mode::Weakness,
+            Some("invalid") => Mode::Invalid,
+            Some(_) => { return Err(ParseConfigError { kind: ParseConfigErrorKind::InvalidMode }); }
+        };
     
-        Ok(Config { filename, preamble_size })
+        Ok(Config { filename, preamble_size, mode })
     }
 }
 
@@ -85,7 +129,8 @@ impl Display for ParseConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", match self.kind {
             ParseConfigErrorKind::NotEnoughArguments => "not enough arguments",
-            ParseConfigErrorKind::InvalidPreambleSize => "couldn't parse preamble size"
+            ParseConfigErrorKind::InvalidPreambleSize => "couldn't parse preamble size",
+            ParseConfigErrorKind::InvalidMode => "mode must be 'invalid' or omitted"
         })
     }
 }
@@ -95,5 +140,6 @@ impl Error for ParseConfigError { }
 #[derive(Debug)]
 pub enum ParseConfigErrorKind {
     NotEnoughArguments,
-    InvalidPreambleSize
+    InvalidPreambleSize,
+    InvalidMode
 }
\ No newline at end of file
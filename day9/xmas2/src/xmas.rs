@@ -1,54 +1,287 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt::Display;
+use std::io::{self, BufRead, Lines};
 
+pub fn find_invalid_number(data: &[i64], preamble_size: usize) -> Option<i64> {
+    let mut validator = XmasValidator::new(preamble_size);
 
-pub fn find_invalid_number(data: &Vec<i64>, preamble_size: usize) -> Option<i64> {
-    for i in preamble_size..data.len() {
-        if !is_valid(&data, preamble_size, i) {
-            return Some(data[i]);
+    data.iter()
+        .find(|value| validator.push(**value) == Validation::Invalid)
+        .cloned()
+}
+
+// Every contiguous range of at least two numbers summing to the target, in
+// order of position. A running prefix sum is kept in i128 so it can't
+// overflow, and each prefix is indexed by value: a range ends at j exactly
+// when some earlier prefix equals prefix(j) - target.
+pub fn find_weakness_ranges(data: &[i64], target: i64) -> Vec<WeaknessRange> {
+    let target = target as i128;
+
+    let mut prefixes: HashMap<i128, Vec<usize>> = HashMap::new();
+    prefixes.insert(0, vec![0]);
+
+    let mut ranges = Vec::new();
+    let mut prefix: i128 = 0;
+
+    for (index, value) in data.iter().enumerate() {
+        prefix += *value as i128;
+
+        if let Some(starts) = prefixes.get(&(prefix - target)) {
+            for start in starts.iter().filter(|start| index > **start) {
+                let range = &data[*start..=index];
+
+                ranges.push(WeaknessRange {
+                    start: start + 1,
+                    end: index + 1,
+                    sum: target,
+                    min: *range.iter().min().unwrap(),
+                    max: *range.iter().max().unwrap()
+                });
+            }
         }
+
+        prefixes.entry(prefix).or_default().push(index + 1);
     }
 
-    None
+    ranges.sort_by_key(|range| (range.start, range.end));
+
+    ranges
 }
 
-pub fn find_weakness(data: &Vec<i64>, target: i64) -> Option<i64> {
-    let len = data.len();
-    let mut start = 0;
-    let mut end = 1;
+// A contiguous range of numbers, by position (counting from 1, inclusive).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeaknessRange {
+    pub start: usize,
+    pub end: usize,
+    pub sum: i128,
+    pub min: i64,
+    pub max: i64
+}
+
+impl WeaknessRange {
+    pub fn weakness(&self) -> i128 {
+        self.min as i128 + self.max as i128
+    }
+}
+
+impl Display for WeaknessRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}: sum {}, min {}, max {}, weakness {}",
+            self.start, self.end, self.sum, self.min, self.max, self.weakness())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Validation {
+    Preamble,
+    Valid,
+    Invalid
+}
+
+// Validates a stream of numbers against the window of the previous
+// `preamble_size` numbers. Alongside the window it keeps a count of every sum
+// of two numbers at different positions in it (equal numbers included), so
+// checking a number is a single lookup and sliding the window only touches the
+// pairs made with the numbers entering and leaving it. Sums are kept in i128
+// so that no pair of i64s can overflow.
+pub struct XmasValidator {
+    preamble_size: usize,
+    window: VecDeque<i64>,
+    pair_sums: HashMap<i128, usize>
+}
 
-    while start < len && end < len {
-        let sum: i64 = data[start..end].iter().sum();
-        if sum == target {
-            let smallest = data[start..end].iter().min()?;
-            let largest = data[start..end].iter().max()?;
-            return Some(smallest + largest);
-        } else if sum < target {
-            end = end + 1;
+impl XmasValidator {
+    pub fn new(preamble_size: usize) -> XmasValidator {
+        XmasValidator {
+            preamble_size,
+            window: VecDeque::with_capacity(preamble_size + 1),
+            pair_sums: HashMap::new()
+        }
+    }
+
+    pub fn push(&mut self, value: i64) -> Validation {
+        let validation = if self.window.len() < self.preamble_size {
+            Validation::Preamble
+        } else if self.pair_sums.contains_key(&(value as i128)) {
+            Validation::Valid
         } else {
-            start = start + 1;
-            if end <= start { 
-                end = start + 1;
+            Validation::Invalid
+        };
+
+        if self.preamble_size == 0 {
+            return validation;
+        }
+
+        if self.window.len() == self.preamble_size {
+            let outgoing = self.window.pop_front().unwrap();
+            for other in self.window.iter() {
+                let sum = outgoing as i128 + *other as i128;
+                let count = self.pair_sums.get_mut(&sum).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.pair_sums.remove(&sum);
+                }
             }
         }
+
+        for other in self.window.iter() {
+            *self.pair_sums.entry(value as i128 + *other as i128).or_insert(0) += 1;
+        }
+        self.window.push_back(value);
+
+        validation
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidNumber {
+    pub position: usize,
+    pub value: i64
+}
+
+impl Display for InvalidNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.position, self.value)
+    }
+}
+
+// Reads one number per line and yields every invalid number along with its
+// position (the line it was read from, counting from 1).
+pub struct InvalidNumbers<R> {
+    lines: Lines<R>,
+    validator: XmasValidator,
+    position: usize
+}
+
+impl<R: BufRead> InvalidNumbers<R> {
+    pub fn new(reader: R, preamble_size: usize) -> InvalidNumbers<R> {
+        InvalidNumbers { lines: reader.lines(), validator: XmasValidator::new(preamble_size), position: 0 }
     }
-    
-    None
 }
 
-fn is_valid(data: &Vec<i64>, preamble_size: usize, i: usize) -> bool {
-    let mut set = HashSet::new();
+impl<R: BufRead> Iterator for InvalidNumbers<R> {
+    type Item = Result<InvalidNumber, ReadXmasError>;
 
-    let num = data[i];
-    for j in (i - preamble_size)..i {
-        let other = data[j];
-        let compliment = num - other;
-        
-        if set.contains(&compliment) {
-            return true;
-        } else {
-            set.insert(other);
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in self.lines.by_ref() {
+            self.position += 1;
+
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => return Some(Err(ReadXmasError::Io(e)))
+            };
+
+            let value = match line.trim().parse::<i64>() {
+                Ok(value) => value,
+                Err(_) => return Some(Err(ReadXmasError::InvalidLine { position: self.position, line }))
+            };
+
+            if self.validator.push(value) == Validation::Invalid {
+                return Some(Ok(InvalidNumber { position: self.position, value }));
+            }
+        }
+
+        None
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadXmasError {
+    Io(io::Error),
+    InvalidLine { position: usize, line: String }
+}
+
+impl Display for ReadXmasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadXmasError::Io(e) => writeln!(f, "{}", e),
+            ReadXmasError::InvalidLine { position, line } => writeln!(f, "line {} isn't a number: {}", position, line)
+        }
+    }
+}
+
+impl Error for ReadXmasError { }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "35\n20\n15\n25\n47\n40\n62\n55\n65\n95\n102\n117\n150\n182\n127\n219\n299\n277\n309\n576\n";
+
+    #[test]
+    fn validator_matches_example() {
+        let data: Vec<i64> = EXAMPLE.lines().map(|line| line.parse().unwrap()).collect();
+
+        assert_eq!(Some(127), find_invalid_number(&data, 5));
+    }
+
+    #[test]
+    fn weakness_ranges_match_example() {
+        let data: Vec<i64> = EXAMPLE.lines().map(|line| line.parse().unwrap()).collect();
+
+        let ranges = find_weakness_ranges(&data, 127);
+
+        assert_eq!(vec![WeaknessRange { start: 3, end: 6, sum: 127, min: 15, max: 47 }], ranges);
+        assert_eq!(62, ranges[0].weakness());
+    }
+
+    #[test]
+    fn weakness_ranges_find_overlapping_ranges() {
+        let ranges = find_weakness_ranges(&[1, 2, 3, 0, 3, -3, 6], 6);
+
+        let bounds: Vec<(usize, usize)> = ranges.iter().map(|range| (range.start, range.end)).collect();
+        assert_eq!(vec![(1, 3), (1, 4), (1, 6), (3, 5), (4, 7), (5, 7)], bounds);
+    }
+
+    #[test]
+    fn weakness_ranges_dont_overflow() {
+        let ranges = find_weakness_ranges(&[i64::MAX, i64::MAX, -i64::MAX, 1], i64::MAX);
+
+        // The running sum passes 2 * i64::MAX before coming back down.
+        assert_eq!(vec![WeaknessRange { start: 1, end: 3, sum: i64::MAX as i128, min: -i64::MAX, max: i64::MAX }], ranges);
+    }
+
+    #[test]
+    fn stream_reports_every_invalid_number() {
+        let invalid: Vec<InvalidNumber> = InvalidNumbers::new(EXAMPLE.as_bytes(), 5)
+            .map(|result| result.unwrap())
+            .collect();
+
+        assert_eq!(vec![InvalidNumber { position: 15, value: 127 }], invalid);
+    }
+
+    #[test]
+    fn validator_accepts_pairs_of_equal_numbers() {
+        // Two equal numbers at different positions are a pair, as in the
+        // original is_valid.
+        let mut validator = XmasValidator::new(2);
+
+        assert_eq!(Validation::Preamble, validator.push(5));
+        assert_eq!(Validation::Preamble, validator.push(5));
+        assert_eq!(Validation::Valid, validator.push(10));
+        assert_eq!(Validation::Valid, validator.push(15));
+        assert_eq!(Validation::Invalid, validator.push(20));
+    }
+
+    #[test]
+    fn validator_pair_sums_dont_overflow() {
+        let mut validator = XmasValidator::new(2);
+
+        assert_eq!(Validation::Preamble, validator.push(i64::MAX));
+        assert_eq!(Validation::Preamble, validator.push(i64::MAX - 1));
+        assert_eq!(Validation::Invalid, validator.push(-2));
+        assert_eq!(Validation::Valid, validator.push(i64::MAX - 3));
+        assert_eq!(Validation::Invalid, validator.push(i64::MIN));
+    }
+
+    #[test]
+    fn stream_reports_bad_lines() {
+        let mut invalid = InvalidNumbers::new("1\n2\nthree\n".as_bytes(), 2);
+
+        match invalid.next() {
+            Some(Err(ReadXmasError::InvalidLine { position, .. })) => assert_eq!(3, position),
+            other => panic!("unexpected result: {:?}", other)
         }
     }
-    
-    false
 }